@@ -0,0 +1,59 @@
+/* svgtidy C API. Generated by cbindgen; run scripts/build-header.sh to update. */
+
+#ifndef SVGTIDY_H
+#define SVGTIDY_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+#if !defined(__wasm32__)
+// Optimizes the `svg_len` bytes of UTF-8 at `svg`.
+//
+// `options_json` is `NULL` for the defaults, or a NUL-terminated JSON object
+// in the `svgtidy.config.json` format plus `enable`/`disable` lists, e.g.
+// `{"precision": 2, "disable": ["removeTitle"]}`.
+//
+// Returns a NUL-terminated string to release with [`svgtidy_free`], and
+// stores its length (without the NUL) in `out_len` unless that is `NULL`.
+// Returns `NULL` on error, leaving `out_len` untouched.
+//
+// # Safety
+//
+// `svg` must point to `svg_len` readable bytes, `options_json` must be
+// `NULL` or a NUL-terminated string, and `out_len` must be `NULL` or
+// writable.
+char *svgtidy_optimize(const uint8_t *svg,
+                       size_t svg_len,
+                       const char *options_json,
+                       size_t *out_len);
+#endif
+
+#if !defined(__wasm32__)
+// Releases a string returned by svgtidy. `NULL` is ignored.
+//
+// # Safety
+//
+// `ptr` must be `NULL` or a pointer returned by svgtidy that has not been
+// freed yet.
+void svgtidy_free(char *ptr);
+#endif
+
+#if !defined(__wasm32__)
+// Describes the last error on the calling thread, or returns `NULL` if the
+// last call succeeded. The string stays valid until the next svgtidy call
+// on the same thread and must not be freed.
+const char *svgtidy_last_error(void);
+#endif
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* SVGTIDY_H */
@@ -0,0 +1,65 @@
+/* eslint-disable */
+
+export type PluginParams = Record<string, boolean | number | string>;
+
+export interface OptimizeOptions {
+  /** Numeric precision. Defaults to 3. */
+  precision?: number;
+  /** Plugins to turn on in addition to the defaults. */
+  enable?: string[];
+  /** Plugins to turn off. */
+  disable?: string[];
+  /** Pretty print instead of minifying. */
+  pretty?: boolean;
+  /** Run exactly these plugins in this order. */
+  order?: string[];
+  /** Re-run the pipeline until the output stops shrinking. */
+  multipass?: boolean;
+  maxPasses?: number;
+  /** Per-plugin toggles or parameters, as in `svgtidy.config.json`. */
+  plugins?: Record<string, boolean | PluginParams>;
+}
+
+export interface OptimizeResult {
+  /** The optimized SVG, or `null` if optimization failed. */
+  data: string | null;
+  error: string | null;
+  /** Input size in bytes (UTF-8). */
+  originalSize: number;
+  /** Output size in bytes (UTF-8), 0 on error. */
+  optimizedSize: number;
+}
+
+export interface PluginParamInfo {
+  name: string;
+  type: 'boolean' | 'integer';
+  description: string;
+  /** `null` when the parameter follows the global `precision`. */
+  default: boolean | number | null;
+}
+
+export interface PluginInfo {
+  name: string;
+  description: string;
+  enabledByDefault: boolean;
+  params: PluginParamInfo[];
+}
+
+/** Optimizes `svg` with the default options. Throws on invalid input. */
+export function optimize(svg: string): string;
+
+/**
+ * Optimizes `svg` with the given options. Errors are reported in `error`
+ * rather than thrown.
+ */
+export function optimizeWithOptions(svg: string, options?: OptimizeOptions): OptimizeResult;
+
+/** The built-in plugins in their default order. */
+export function listPlugins(): PluginInfo[];
+
+/**
+ * Optimizes every SVG on a worker thread, spreading the work over all cores.
+ * Resolves to one result per input, in input order. Invalid options throw
+ * right away instead of failing every result.
+ */
+export function optimizeBatch(svgs: string[], options?: OptimizeOptions): Promise<OptimizeResult[]>;
@@ -0,0 +1,13 @@
+const { join } = require('node:path');
+
+const binding = `svgtidy.${process.platform}-${process.arch}.node`;
+
+try {
+  module.exports = require(join(__dirname, binding));
+} catch (err) {
+  throw new Error(
+    `svgtidy-node: no native build for ${process.platform}-${process.arch} (${binding}). ` +
+      'Run `npm run build` in the package, or use the `svgtidy` WASM package instead.',
+    { cause: err },
+  );
+}
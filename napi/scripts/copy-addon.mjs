@@ -0,0 +1,17 @@
+// Copies the compiled addon next to index.js under its platform-specific name.
+import { copyFileSync } from 'node:fs';
+import { dirname, join } from 'node:path';
+import { fileURLToPath } from 'node:url';
+
+const profile = process.argv[2] ?? 'release';
+const root = dirname(dirname(fileURLToPath(import.meta.url)));
+const library = {
+  win32: 'svgtidy_node.dll',
+  darwin: 'libsvgtidy_node.dylib',
+}[process.platform] ?? 'libsvgtidy_node.so';
+
+const target = process.env.CARGO_TARGET_DIR ?? join(root, '..', 'target');
+const from = join(target, profile, library);
+const to = join(root, `svgtidy.${process.platform}-${process.arch}.node`);
+copyFileSync(from, to);
+console.log(`${from} -> ${to}`);
@@ -0,0 +1,98 @@
+//! Native Node.js bindings. Mirrors the WASM exports and adds
+//! `optimizeBatch`, which optimizes many files in parallel off the main
+//! thread.
+
+use napi::bindgen_prelude::AsyncTask;
+use napi::{Env, Task};
+use napi_derive::napi;
+use rayon::prelude::*;
+use svgtidy::bindings::{self, plugin_catalog, OptimizeResult, Options};
+use svgtidy::pipeline::OptimizeOptions;
+
+fn to_napi_error(error: impl ToString) -> napi::Error {
+    napi::Error::from_reason(error.to_string())
+}
+
+fn parse_options(options: Option<serde_json::Value>) -> svgtidy::Result<Options> {
+    match options {
+        None | Some(serde_json::Value::Null) => Ok(Options::default()),
+        Some(value) => Options::from_json_value(value),
+    }
+}
+
+#[napi(object, js_name = "OptimizeResult", use_nullable = true)]
+pub struct JsOptimizeResult {
+    pub data: Option<String>,
+    pub error: Option<String>,
+    pub original_size: u32,
+    pub optimized_size: u32,
+}
+
+impl From<OptimizeResult> for JsOptimizeResult {
+    fn from(result: OptimizeResult) -> Self {
+        Self {
+            data: result.data,
+            error: result.error,
+            original_size: result.original_size as u32,
+            optimized_size: result.optimized_size as u32,
+        }
+    }
+}
+
+/// Optimizes `svg` with the default options. Throws on invalid input.
+#[napi]
+pub fn optimize(svg: String) -> napi::Result<String> {
+    svgtidy::optimize_with_options(&svg, &OptimizeOptions::default()).map_err(to_napi_error)
+}
+
+/// Optimizes `svg` with the given options. Errors are reported in `error`
+/// rather than thrown.
+#[napi]
+pub fn optimize_with_options(
+    svg: String,
+    #[napi(ts_arg_type = "OptimizeOptions")] options: Option<serde_json::Value>,
+) -> JsOptimizeResult {
+    let output = parse_options(options).and_then(|options| bindings::optimize(&svg, &options));
+    OptimizeResult::new(&svg, output).into()
+}
+
+/// The built-in plugins in their default order.
+#[napi(ts_return_type = "PluginInfo[]")]
+pub fn list_plugins() -> napi::Result<serde_json::Value> {
+    serde_json::to_value(plugin_catalog()).map_err(to_napi_error)
+}
+
+pub struct OptimizeBatch {
+    svgs: Vec<String>,
+    options: Options,
+}
+
+impl Task for OptimizeBatch {
+    type Output = Vec<JsOptimizeResult>;
+    type JsValue = Vec<JsOptimizeResult>;
+
+    fn compute(&mut self) -> napi::Result<Self::Output> {
+        Ok(self
+            .svgs
+            .par_iter()
+            .map(|svg| OptimizeResult::new(svg, bindings::optimize(svg, &self.options)).into())
+            .collect())
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Optimizes every SVG on a worker thread, spreading the work over all
+/// cores. Resolves to one result per input, in input order. Invalid options
+/// throw right away instead of failing every result.
+#[napi]
+pub fn optimize_batch(
+    svgs: Vec<String>,
+    #[napi(ts_arg_type = "OptimizeOptions")] options: Option<serde_json::Value>,
+) -> napi::Result<AsyncTask<OptimizeBatch>> {
+    let options = parse_options(options).map_err(to_napi_error)?;
+    options.to_options().map_err(to_napi_error)?;
+    Ok(AsyncTask::new(OptimizeBatch { svgs, options }))
+}
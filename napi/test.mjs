@@ -0,0 +1,26 @@
+import assert from 'node:assert/strict';
+import { createRequire } from 'node:module';
+
+const require = createRequire(import.meta.url);
+const { optimize, optimizeWithOptions, optimizeBatch, listPlugins } = require('./index.js');
+
+assert.equal(optimize('<svg><!-- c --><rect/></svg>'), '<svg><rect/></svg>');
+assert.throws(() => optimize('<svg>'), /unclosed element <svg>/);
+
+const result = optimizeWithOptions('<svg><title>t</title></svg>', { disable: ['removeTitle'] });
+assert.deepEqual(result, {
+  data: '<svg><title>t</title></svg>',
+  error: null,
+  originalSize: 27,
+  optimizedSize: 27,
+});
+assert.match(optimizeWithOptions('<svg/>', { precison: 2 }).error, /unknown field `precison`/);
+
+const results = await optimizeBatch(['<svg><desc>d</desc></svg>', '<svg>'], { precision: 2 });
+assert.equal(results[0].data, '<svg/>');
+assert.match(results[1].error, /unclosed element/);
+assert.throws(() => optimizeBatch([], { enable: ['noSuchPlugin'] }), /unknown plugin/);
+
+assert.ok(listPlugins().some((plugin) => plugin.name === 'convertPathData'));
+
+console.log('svgtidy-node: all tests passed');
@@ -1,15 +1,23 @@
 // Webpack loader context type
 interface LoaderContext {
     async: () => (err: Error | null, result?: string) => void;
+    getOptions?: () => SvgtidyLoaderOptions;
     resourcePath: string;
 }
 
+export interface SvgtidyLoaderOptions {
+    /** Use the native `svgtidy-node` addon instead of the WASM build. */
+    native?: boolean;
+}
+
 export default async function svgtidyLoader(this: LoaderContext, source: string) {
     const callback = this.async();
     
     try {
+        const { native = false } = this.getOptions?.() ?? {};
         // @ts-ignore
-        const { optimize } = await import('svgtidy');
+        const mod = await import(native ? 'svgtidy-node' : 'svgtidy');
+        const { optimize } = mod.default ?? mod;
         const optimized = optimize(source);
         callback(null, optimized);
     } catch (err: any) {
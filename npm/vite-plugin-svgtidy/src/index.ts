@@ -1,8 +1,23 @@
 import type { Plugin } from 'vite';
-import { optimize } from 'svgtidy';
+import { optimize as optimizeWasm } from 'svgtidy';
 import fs from 'node:fs';
+import { createRequire } from 'node:module';
 
-export default function svgtidyPlugin(options: any = {}): Plugin {
+export interface SvgtidyPluginOptions {
+  /** Use the native `svgtidy-node` addon instead of the WASM build. */
+  native?: boolean;
+}
+
+function loadOptimize(native: boolean): (svg: string) => string {
+  if (!native) {
+    return optimizeWasm;
+  }
+  const require = createRequire(import.meta.url);
+  return require('svgtidy-node').optimize;
+}
+
+export default function svgtidyPlugin(options: SvgtidyPluginOptions = {}): Plugin {
+  const optimize = loadOptimize(options.native ?? false);
   return {
     name: 'vite-plugin-svgtidy',
     enforce: 'pre',
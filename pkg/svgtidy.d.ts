@@ -1,4 +1,63 @@
 /* tslint:disable */
 /* eslint-disable */
 
+export type PluginParams = Record<string, boolean | number | string>;
+
+export interface OptimizeOptions {
+  /** Numeric precision. Defaults to 3. */
+  precision?: number;
+  /** Plugins to turn on in addition to the defaults. */
+  enable?: string[];
+  /** Plugins to turn off. */
+  disable?: string[];
+  /** Pretty print instead of minifying. */
+  pretty?: boolean;
+  /** Run exactly these plugins in this order. */
+  order?: string[];
+  /** Re-run the pipeline until the output stops shrinking. */
+  multipass?: boolean;
+  maxPasses?: number;
+  /** Per-plugin toggles or parameters, as in `svgtidy.config.json`. */
+  plugins?: Record<string, boolean | PluginParams>;
+}
+
+export interface OptimizeResult {
+  /** The optimized SVG, or `null` if optimization failed. */
+  data: string | null;
+  error: string | null;
+  /** Input size in bytes (UTF-8). */
+  originalSize: number;
+  /** Output size in bytes (UTF-8), 0 on error. */
+  optimizedSize: number;
+}
+
+/**
+ * Optimizes `svg` with the given options. Errors (invalid SVG, unknown
+ * plugins, bad options) are reported in `error` rather than thrown.
+ */
+export function optimizeWithOptions(svg: string, options?: OptimizeOptions): OptimizeResult;
+
+export interface PluginParamInfo {
+  name: string;
+  type: "boolean" | "integer";
+  description: string;
+  /** `null` when the parameter follows the global `precision`. */
+  default: boolean | number | null;
+}
+
+export interface PluginInfo {
+  name: string;
+  description: string;
+  enabledByDefault: boolean;
+  params: PluginParamInfo[];
+}
+
+/** The built-in plugins in their default order. */
+export function listPlugins(): PluginInfo[];
+
+
+
+/**
+ * JavaScript entry point. Unlike [`optimize`], this throws on invalid input.
+ */
 export function optimize(svg: string): string;
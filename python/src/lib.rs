@@ -0,0 +1,70 @@
+//! Native part of the `svgtidy` Python package. The public API, with keyword
+//! arguments for the options, lives in `svgtidy/__init__.py`; options cross
+//! over as JSON in the `svgtidy::bindings::Options` format.
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rayon::prelude::*;
+use svgtidy::bindings::{self, plugin_catalog, Options};
+
+create_exception!(
+    _svgtidy,
+    SvgtidyError,
+    PyValueError,
+    "Raised when an SVG cannot be parsed or the options are invalid."
+);
+
+fn to_py_error(error: svgtidy::Error) -> PyErr {
+    SvgtidyError::new_err(error.to_string())
+}
+
+/// Parses and validates the options up front, so a batch fails once for bad
+/// options instead of once per input.
+fn parse_options(options_json: Option<&str>) -> PyResult<Options> {
+    let options = match options_json {
+        Some(json) => Options::from_json(json).map_err(to_py_error)?,
+        None => Options::default(),
+    };
+    options.to_options().map_err(to_py_error)?;
+    Ok(options)
+}
+
+#[pyfunction]
+#[pyo3(signature = (svg, options_json=None))]
+fn optimize(py: Python<'_>, svg: &str, options_json: Option<&str>) -> PyResult<String> {
+    let options = parse_options(options_json)?;
+    py.detach(|| bindings::optimize(svg, &options))
+        .map_err(to_py_error)
+}
+
+#[pyfunction]
+#[pyo3(signature = (svgs, options_json=None))]
+fn optimize_batch(
+    py: Python<'_>,
+    svgs: Vec<String>,
+    options_json: Option<&str>,
+) -> PyResult<Vec<String>> {
+    let options = parse_options(options_json)?;
+    py.detach(|| {
+        svgs.par_iter()
+            .enumerate()
+            .map(|(index, svg)| bindings::optimize(svg, &options).map_err(|error| (index, error)))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .map_err(|(index, error)| SvgtidyError::new_err(format!("input {index}: {error}")))
+}
+
+#[pyfunction]
+fn list_plugins_json() -> String {
+    serde_json::to_string(&plugin_catalog()).expect("plugin catalog serializes to JSON")
+}
+
+#[pymodule]
+fn _svgtidy(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("SvgtidyError", m.py().get_type::<SvgtidyError>())?;
+    m.add_function(wrap_pyfunction!(optimize, m)?)?;
+    m.add_function(wrap_pyfunction!(optimize_batch, m)?)?;
+    m.add_function(wrap_pyfunction!(list_plugins_json, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+"""High-performance SVG optimizer written in Rust.
+
+>>> import svgtidy
+>>> svgtidy.optimize('<svg><title>icon</title><rect/></svg>')
+'<svg><rect/></svg>'
+"""
+
+import json
+from typing import Any, Dict, Iterable, List, Optional, Union
+
+from . import _svgtidy
+from ._svgtidy import SvgtidyError
+
+__all__ = ["SvgtidyError", "list_plugins", "optimize", "optimize_batch"]
+
+PluginParams = Dict[str, Union[bool, int, str]]
+
+
+def _options_json(
+    precision: Optional[int],
+    enable: Optional[Iterable[str]],
+    disable: Optional[Iterable[str]],
+    pretty: Optional[bool],
+    order: Optional[Iterable[str]],
+    multipass: Optional[bool],
+    max_passes: Optional[int],
+    plugins: Optional[Dict[str, Union[bool, PluginParams]]],
+) -> Optional[str]:
+    options = {
+        "precision": precision,
+        "enable": None if enable is None else list(enable),
+        "disable": None if disable is None else list(disable),
+        "pretty": pretty,
+        "order": None if order is None else list(order),
+        "multipass": multipass,
+        "maxPasses": max_passes,
+        "plugins": plugins,
+    }
+    options = {name: value for name, value in options.items() if value is not None}
+    return json.dumps(options) if options else None
+
+
+def optimize(
+    svg: str,
+    *,
+    precision: Optional[int] = None,
+    enable: Optional[Iterable[str]] = None,
+    disable: Optional[Iterable[str]] = None,
+    pretty: Optional[bool] = None,
+    order: Optional[Iterable[str]] = None,
+    multipass: Optional[bool] = None,
+    max_passes: Optional[int] = None,
+    plugins: Optional[Dict[str, Union[bool, PluginParams]]] = None,
+) -> str:
+    """Optimizes ``svg`` and returns the result.
+
+    The options match the CLI and ``svgtidy.config.json``; ``plugins`` maps a
+    plugin name to ``True``/``False`` or to a dict of parameters. The GIL is
+    released while optimizing. Raises :class:`SvgtidyError` for input that is
+    not well-formed XML and for invalid options.
+    """
+    options = _options_json(
+        precision, enable, disable, pretty, order, multipass, max_passes, plugins
+    )
+    return _svgtidy.optimize(svg, options)
+
+
+def optimize_batch(
+    svgs: Iterable[str],
+    *,
+    precision: Optional[int] = None,
+    enable: Optional[Iterable[str]] = None,
+    disable: Optional[Iterable[str]] = None,
+    pretty: Optional[bool] = None,
+    order: Optional[Iterable[str]] = None,
+    multipass: Optional[bool] = None,
+    max_passes: Optional[int] = None,
+    plugins: Optional[Dict[str, Union[bool, PluginParams]]] = None,
+) -> List[str]:
+    """Optimizes many SVGs in parallel on all cores, returning the results in
+    input order. Takes the same options as :func:`optimize`.
+
+    Raises :class:`SvgtidyError` naming the index of an input that fails.
+    """
+    options = _options_json(
+        precision, enable, disable, pretty, order, multipass, max_passes, plugins
+    )
+    return _svgtidy.optimize_batch(list(svgs), options)
+
+
+def list_plugins() -> List[Dict[str, Any]]:
+    """Returns the built-in plugins in their default order, each with its
+    ``name``, ``description``, ``enabledByDefault`` and ``params``."""
+    return json.loads(_svgtidy.list_plugins_json())
@@ -0,0 +1,7 @@
+from typing import List, Optional
+
+class SvgtidyError(ValueError): ...
+
+def optimize(svg: str, options_json: Optional[str] = None) -> str: ...
+def optimize_batch(svgs: List[str], options_json: Optional[str] = None) -> List[str]: ...
+def list_plugins_json() -> str: ...
@@ -0,0 +1,46 @@
+import unittest
+
+import svgtidy
+
+
+class OptimizeTest(unittest.TestCase):
+    def test_optimize_with_defaults(self):
+        self.assertEqual(
+            svgtidy.optimize("<svg><title>t</title><rect/></svg>"), "<svg><rect/></svg>"
+        )
+
+    def test_optimize_with_options(self):
+        svg = '<svg><title>t</title><path d="M0.12345 0"/></svg>'
+        self.assertEqual(
+            svgtidy.optimize(svg, precision=1, disable=["removeTitle"]),
+            '<svg><title>t</title><path d="M.1 0"/></svg>',
+        )
+        self.assertEqual(
+            svgtidy.optimize(svg, plugins={"convertPathData": {"floatPrecision": 2}}),
+            '<svg><path d="M.12 0"/></svg>',
+        )
+
+    def test_errors(self):
+        with self.assertRaisesRegex(svgtidy.SvgtidyError, "unclosed element <svg>"):
+            svgtidy.optimize("<svg>")
+        with self.assertRaisesRegex(svgtidy.SvgtidyError, "unknown plugin"):
+            svgtidy.optimize("<svg/>", enable=["noSuchPlugin"])
+        self.assertTrue(issubclass(svgtidy.SvgtidyError, ValueError))
+
+    def test_optimize_batch(self):
+        svgs = ["<svg><desc>d</desc></svg>", "<svg><!-- c --><rect/></svg>"] * 50
+        self.assertEqual(
+            svgtidy.optimize_batch(svgs, precision=2),
+            ["<svg/>", "<svg><rect/></svg>"] * 50,
+        )
+        with self.assertRaisesRegex(svgtidy.SvgtidyError, "input 1: "):
+            svgtidy.optimize_batch(["<svg/>", "<svg>"])
+
+    def test_list_plugins(self):
+        plugins = {plugin["name"]: plugin for plugin in svgtidy.list_plugins()}
+        self.assertFalse(plugins["mergePaths"]["enabledByDefault"])
+        self.assertEqual(plugins["convertPathData"]["params"][0]["name"], "floatPrecision")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,5 @@
+#!/usr/bin/env sh
+
+set -eu
+
+cbindgen --config cbindgen.toml --output include/svgtidy.h
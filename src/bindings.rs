@@ -0,0 +1,220 @@
+//! Pieces shared by the language bindings (WASM, Node, ...): options in the
+//! config file shape, a result that reports errors as data, and the plugin
+//! catalog.
+
+use crate::config::{Config, PluginSetting};
+use crate::error::{Error, Result};
+use crate::pipeline::{
+    plugin_descriptors, validate_options, OptimizeOptions, ParamDefault, ParamKind,
+};
+use crate::{optimize_to_document, printer};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// Options as passed from another language: the config file format plus
+/// `enable`/`disable` lists, which win over `plugins`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Options {
+    pub precision: Option<usize>,
+    pub pretty: Option<bool>,
+    pub order: Option<Vec<String>>,
+    pub multipass: Option<bool>,
+    pub max_passes: Option<usize>,
+    #[serde(default)]
+    pub enable: Vec<String>,
+    #[serde(default)]
+    pub disable: Vec<String>,
+    #[serde(default)]
+    pub plugins: IndexMap<String, PluginSetting>,
+}
+
+impl Options {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|e| Error::invalid_config(e.to_string()))
+    }
+
+    pub fn from_json_value(value: serde_json::Value) -> Result<Self> {
+        serde_json::from_value(value).map_err(|e| Error::invalid_config(e.to_string()))
+    }
+
+    pub fn to_options(&self) -> Result<OptimizeOptions> {
+        let config = Config {
+            precision: self.precision,
+            pretty: self.pretty,
+            order: self.order.clone(),
+            multipass: self.multipass,
+            max_passes: self.max_passes,
+            plugins: self.plugins.clone(),
+        };
+        let mut options = config.to_options()?;
+        for name in &self.enable {
+            options.disable.remove(name);
+            options.enable.insert(name.clone());
+        }
+        for name in &self.disable {
+            options.enable.remove(name);
+            options.disable.insert(name.clone());
+        }
+        validate_options(&options)?;
+        Ok(options)
+    }
+}
+
+/// Optimizes `svg`, pretty printing when `options.pretty` is set.
+pub fn optimize(svg: &str, options: &Options) -> Result<String> {
+    let doc = optimize_to_document(svg, &options.to_options()?)?;
+    if options.pretty.unwrap_or(false) {
+        Ok(printer::print_pretty(&doc))
+    } else {
+        Ok(printer::print(&doc))
+    }
+}
+
+/// Sizes are in UTF-8 bytes; `optimized_size` is 0 when `error` is set.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptimizeResult {
+    pub data: Option<String>,
+    pub error: Option<String>,
+    pub original_size: usize,
+    pub optimized_size: usize,
+}
+
+impl OptimizeResult {
+    /// Wraps the outcome of optimizing `svg`, e.g. from [`optimize`].
+    pub fn new(svg: &str, output: Result<String>) -> Self {
+        let (data, error) = match output {
+            Ok(data) => (Some(data), None),
+            Err(error) => (None, Some(error.to_string())),
+        };
+        Self {
+            optimized_size: data.as_ref().map_or(0, String::len),
+            original_size: svg.len(),
+            data,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub enabled_by_default: bool,
+    pub params: Vec<ParamInfo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamInfo {
+    pub name: &'static str,
+    /// `"boolean"` or `"integer"`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub description: &'static str,
+    /// `null` when the parameter follows the global precision.
+    pub default: serde_json::Value,
+}
+
+/// The built-in plugins in their default order.
+pub fn plugin_catalog() -> Vec<PluginInfo> {
+    plugin_descriptors()
+        .iter()
+        .map(|plugin| PluginInfo {
+            name: plugin.name,
+            description: plugin.description,
+            enabled_by_default: plugin.enabled_by_default,
+            params: plugin
+                .params
+                .iter()
+                .map(|param| ParamInfo {
+                    name: param.name,
+                    kind: match param.kind {
+                        ParamKind::Bool => "boolean",
+                        ParamKind::Integer => "integer",
+                    },
+                    description: param.description,
+                    default: match param.default {
+                        ParamDefault::Bool(value) => value.into(),
+                        ParamDefault::Precision => serde_json::Value::Null,
+                    },
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_optimize_result_with_options() {
+        let svg = "<svg><title>t</title><path d=\"M0.12345 0\"/></svg>";
+        let options =
+            Options::from_json(r#"{ "precision": 1, "disable": ["removeTitle"], "pretty": true }"#)
+                .unwrap();
+        let result = OptimizeResult::new(svg, optimize(svg, &options));
+
+        assert_eq!(result.error, None);
+        let data = result.data.unwrap();
+        assert_eq!(
+            data,
+            "<svg>\n  <title>t</title>\n  <path d=\"M.1 0\"/>\n</svg>\n"
+        );
+        assert_eq!(result.original_size, svg.len());
+        assert_eq!(result.optimized_size, data.len());
+    }
+
+    #[test]
+    fn test_optimize_result_reports_errors() {
+        let result = OptimizeResult::new("<svg>", optimize("<svg>", &Options::default()));
+        assert_eq!(result.data, None);
+        assert_eq!(
+            result.error.as_deref(),
+            Some("parse error at 1:6: unclosed element <svg>")
+        );
+        assert_eq!(result.optimized_size, 0);
+
+        let options = Options::from_json(r#"{ "enable": ["noSuchPlugin"] }"#).unwrap();
+        let result = OptimizeResult::new("<svg/>", optimize("<svg/>", &options));
+        assert_eq!(
+            result.error.as_deref(),
+            Some("unknown plugin(s): noSuchPlugin")
+        );
+
+        let error = Options::from_json(r#"{ "precison": 2 }"#).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid config: unknown field"));
+    }
+
+    #[test]
+    fn test_plugin_catalog() {
+        let catalog = serde_json::to_value(plugin_catalog()).unwrap();
+        let plugins = catalog.as_array().unwrap();
+        assert_eq!(plugins.len(), plugin_descriptors().len());
+        assert_eq!(plugins[0]["name"], "removeDoctype");
+        assert_eq!(plugins[0]["enabledByDefault"], true);
+
+        let cleanup = plugins
+            .iter()
+            .find(|plugin| plugin["name"] == "cleanupNumericValues")
+            .unwrap();
+        assert_eq!(cleanup["params"][0]["name"], "floatPrecision");
+        assert_eq!(cleanup["params"][0]["type"], "integer");
+        assert_eq!(cleanup["params"][0]["default"], serde_json::Value::Null);
+        assert_eq!(cleanup["params"][1]["name"], "removePx");
+        assert_eq!(cleanup["params"][1]["default"], true);
+
+        let merge_paths = plugins
+            .iter()
+            .find(|plugin| plugin["name"] == "mergePaths")
+            .unwrap();
+        assert_eq!(merge_paths["enabledByDefault"], false);
+        assert!(plugins
+            .iter()
+            .all(|plugin| !plugin["description"].as_str().unwrap().is_empty()));
+    }
+}
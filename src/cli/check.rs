@@ -0,0 +1,73 @@
+//! `--check`: find files that are not optimized yet, without writing anything.
+
+use crate::cli::report::{format_change, saved_percent, FileReport};
+
+/// Whether a file fails the check. Without a threshold any change fails;
+/// with one, only files that would shrink by more than `threshold` percent.
+/// Trailing whitespace is ignored on both sides, since editors and `git` tend
+/// to add a final newline and pretty-printed output ends with one.
+pub fn needs_optimization(original: &str, optimized: &str, threshold: Option<f64>) -> bool {
+    let original = original.trim_end();
+    let optimized = optimized.trim_end();
+    match threshold {
+        None => original != optimized,
+        Some(threshold) => saved_percent(original.len(), optimized.len()) > threshold,
+    }
+}
+
+/// Lists the offending files, then a one-line summary.
+pub fn format_check(failures: &[FileReport], checked: usize) -> String {
+    let mut out = String::new();
+    for file in failures {
+        out.push_str(&format!(
+            "would optimize {}: {}\n",
+            file.path,
+            format_change(file.original_size, file.optimized_size)
+        ));
+    }
+    if failures.is_empty() {
+        out.push_str(&format!("{checked} checked, all optimized"));
+    } else {
+        out.push_str(&format!(
+            "{} of {checked} checked would change",
+            failures.len()
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_needs_optimization() {
+        assert!(!needs_optimization("<svg/>\n", "<svg/>", None));
+        // `--pretty` output ends in a newline, whether or not the file does.
+        assert!(!needs_optimization("<svg/>", "<svg/>\n", None));
+        assert!(!needs_optimization("<svg/>\n", "<svg/>\n", None));
+        assert!(needs_optimization("<svg></svg>", "<svg/>", None));
+
+        let original = format!("<svg>{}</svg>", " ".repeat(89));
+        assert!(needs_optimization(&original, "<svg/>", None));
+        assert!(needs_optimization(&original, "<svg/>", Some(50.0)));
+        assert!(!needs_optimization("<svg></svg>", "<svg/>", Some(50.0)));
+    }
+
+    #[test]
+    fn test_format_check() {
+        let failures = [FileReport::new(Path::new("a.svg"), 200, 150)];
+        assert_eq!(
+            format_check(&failures, 3),
+            "would optimize a.svg: 200 B -> 150 B (-25.0%)\n1 of 3 checked would change"
+        );
+        // Files that would grow report a positive change.
+        let failures = [FileReport::new(Path::new("b.svg"), 160, 175)];
+        assert_eq!(
+            format_check(&failures, 1),
+            "would optimize b.svg: 160 B -> 175 B (+9.4%)\n1 of 1 checked would change"
+        );
+        assert_eq!(format_check(&[], 3), "3 checked, all optimized");
+    }
+}
@@ -0,0 +1,59 @@
+//! `--diff`: show what the pipeline changes, as a unified diff of the
+//! pretty-printed input and output.
+
+use similar::TextDiff;
+use svgtidy::tree::Document;
+use svgtidy::{parser, printer, Result};
+
+/// Lines of unchanged context around each hunk.
+const CONTEXT_LINES: usize = 3;
+
+/// Diffs `original` against `optimized`, both pretty-printed so that the
+/// diff shows structural changes rather than one long changed line.
+/// Returns an empty string when nothing changed.
+pub fn pretty_diff(original: &str, optimized: &Document, name: &str) -> Result<String> {
+    let before = printer::print_pretty(&parser::parse(original)?);
+    let after = printer::print_pretty(optimized);
+    Ok(unified_diff(&before, &after, name))
+}
+
+pub fn unified_diff(before: &str, after: &str, name: &str) -> String {
+    if before == after {
+        return String::new();
+    }
+    TextDiff::from_lines(before, after)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(&format!("a/{name}"), &format!("b/{name}"))
+        .missing_newline_hint(false)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgtidy::optimize_to_document;
+    use svgtidy::pipeline::OptimizeOptions;
+
+    #[test]
+    fn test_pretty_diff() {
+        let input = "<svg><!-- note --><rect width=\"10px\" height=\"10\"/></svg>";
+        let doc = optimize_to_document(input, &OptimizeOptions::default()).unwrap();
+
+        let diff = pretty_diff(input, &doc, "icon.svg").unwrap();
+        assert!(
+            diff.starts_with("--- a/icon.svg\n+++ b/icon.svg\n@@"),
+            "{diff}"
+        );
+        assert!(diff.contains("\n-  <!-- note -->\n"), "{diff}");
+        assert!(
+            diff.contains("\n+  <rect height=\"10\" width=\"10\"/>\n"),
+            "{diff}"
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_is_empty_without_changes() {
+        assert_eq!(unified_diff("<svg/>\n", "<svg/>\n", "icon.svg"), "");
+    }
+}
@@ -0,0 +1,56 @@
+//! `--explain`: which plugin changed what.
+
+use std::fmt::Write;
+use svgtidy::trace::Trace;
+
+/// Lists each plugin that changed the document with its savings and edits.
+pub fn format_trace(name: &str, trace: &Trace) -> String {
+    let mut out = format!("{name}\n");
+    let multipass = trace.steps.iter().any(|step| step.pass > 0);
+    let mut changed = false;
+
+    for step in trace.changes() {
+        changed = true;
+        let _ = write!(out, "  {}", step.plugin);
+        if multipass {
+            let _ = write!(out, " (pass {})", step.pass + 1);
+        }
+        let _ = write!(out, ": {:+} B", -step.bytes_saved());
+        if step.reverted {
+            out.push_str(", reverted");
+        }
+        out.push('\n');
+        for edit in &step.edits {
+            let _ = writeln!(out, "    {edit}");
+        }
+    }
+
+    if !changed {
+        out.push_str("  no changes\n");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgtidy::pipeline::OptimizeOptions;
+    use svgtidy::trace::optimize_with_trace;
+
+    #[test]
+    fn test_format_trace() {
+        let options = OptimizeOptions {
+            order: Some(vec!["removeComments".to_string(), "removeDesc".to_string()]),
+            ..OptimizeOptions::default()
+        };
+        let (_, trace) = optimize_with_trace("<svg><!--x--><rect/></svg>", &options).unwrap();
+
+        assert_eq!(
+            format_trace("icon.svg", &trace),
+            "icon.svg\n  removeComments: -8 B\n    removed comment \"x\" from <svg> (1:1)\n"
+        );
+
+        let (_, trace) = optimize_with_trace("<svg/>", &options).unwrap();
+        assert_eq!(format_trace("icon.svg", &trace), "icon.svg\n  no changes\n");
+    }
+}
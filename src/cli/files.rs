@@ -0,0 +1,411 @@
+//! Expands the command-line inputs into the SVG files to process, and reads
+//! and writes them (transparently handling gzip-compressed `.svgz`).
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use svgtidy::{Error, Result};
+use tempfile::NamedTempFile;
+
+/// Extra ignore file honored next to `.gitignore` and `.ignore`.
+const IGNORE_FILE_NAME: &str = ".svgtidyignore";
+
+#[derive(Debug, Clone, Default)]
+pub struct InputOptions {
+    /// Glob patterns matched against paths relative to their input root.
+    pub exclude: Vec<String>,
+    /// Also pick up `.svgz` files when walking directories.
+    pub svgz: bool,
+    /// Don't honor `.gitignore`, `.ignore` and `.svgtidyignore` files.
+    pub no_ignore: bool,
+}
+
+/// A file to optimize and the root its output path is mirrored from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub base: PathBuf,
+}
+
+impl InputFile {
+    pub fn relative(&self) -> &Path {
+        self.path.strip_prefix(&self.base).unwrap_or(&self.path)
+    }
+}
+
+pub fn is_glob(input: &Path) -> bool {
+    input
+        .to_str()
+        .is_some_and(|input| input.contains(['*', '?', '[']))
+}
+
+/// Expands directories and glob patterns. Directory walks keep only SVG
+/// files; files named explicitly are taken as they are.
+pub fn collect_inputs(inputs: &[PathBuf], options: &InputOptions) -> Result<Vec<InputFile>> {
+    let exclude = build_exclude(&options.exclude)?;
+    let mut files = Vec::new();
+
+    for input in inputs {
+        if is_glob(input) && !input.exists() {
+            let pattern = input.to_string_lossy();
+            let base = glob_base(input);
+            let paths = glob::glob(&pattern).map_err(|e| invalid_input(input, e.to_string()))?;
+            let visible = (!options.no_ignore).then(|| visible_paths(&base, options, &exclude));
+            let mut matched = false;
+            for path in paths.flatten() {
+                matched = true;
+                if visible
+                    .as_ref()
+                    .is_some_and(|visible| !visible.contains(&normalize(&path)))
+                {
+                    continue;
+                }
+                if path.is_dir() {
+                    walk_dir(&path, &base, options, &exclude, &mut files);
+                } else if is_svg_path(&path, options.svgz) {
+                    push_file(&mut files, &exclude, path, &base);
+                }
+            }
+            if !matched {
+                return Err(invalid_input(input, "no files match this pattern"));
+            }
+        } else if input.is_dir() {
+            walk_dir(input, input, options, &exclude, &mut files);
+        } else if input.is_file() {
+            let base = input.parent().unwrap_or(Path::new("")).to_path_buf();
+            push_file(&mut files, &exclude, input.clone(), &base);
+        } else {
+            return Err(io_error(input, io::Error::from(io::ErrorKind::NotFound)));
+        }
+    }
+
+    let mut seen = HashSet::new();
+    files.retain(|file| seen.insert(file.path.clone()));
+    Ok(files)
+}
+
+fn build_exclude(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob =
+            Glob::new(pattern).map_err(|e| invalid_input(Path::new(pattern), e.to_string()))?;
+        builder.add(glob);
+    }
+    builder
+        .build()
+        .map_err(|e| invalid_input(Path::new("--exclude"), e.to_string()))
+}
+
+/// The literal directory prefix of a glob, e.g. `icons` for `icons/**/*.svg`.
+fn glob_base(pattern: &Path) -> PathBuf {
+    let mut base = PathBuf::new();
+    for component in pattern.components() {
+        if is_glob(Path::new(component.as_os_str())) {
+            break;
+        }
+        base.push(component);
+    }
+    if base == pattern || base.as_os_str().is_empty() {
+        base = pattern.parent().unwrap_or(Path::new("")).to_path_buf();
+    }
+    base
+}
+
+/// Drops `.` components, so `./icons/a.svg` and `icons/a.svg` compare equal
+/// and patterns like `vendor/**` match.
+fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .collect()
+}
+
+/// Whether `path` or one of its directories below `base` is excluded, so
+/// `--exclude vendor` also drops `vendor/icon.svg`.
+fn is_excluded(exclude: &GlobSet, path: &Path, base: &Path) -> bool {
+    let relative = normalize(path.strip_prefix(base).unwrap_or(path));
+    relative
+        .ancestors()
+        .take_while(|ancestor| !ancestor.as_os_str().is_empty())
+        .any(|ancestor| exclude.is_match(ancestor))
+}
+
+fn push_file(files: &mut Vec<InputFile>, exclude: &GlobSet, path: PathBuf, base: &Path) {
+    if !is_excluded(exclude, &path, base) {
+        files.push(InputFile {
+            path,
+            base: base.to_path_buf(),
+        });
+    }
+}
+
+/// Everything under `base` that survives the ignore files and `--exclude`;
+/// glob matches outside this set are dropped like they are in directory walks.
+fn visible_paths(base: &Path, options: &InputOptions, exclude: &GlobSet) -> HashSet<PathBuf> {
+    let root = if base.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        base
+    };
+    walk_builder(root, base, options, exclude)
+        .build()
+        .flatten()
+        .map(|entry| normalize(entry.path()))
+        .collect()
+}
+
+fn walk_dir(
+    dir: &Path,
+    base: &Path,
+    options: &InputOptions,
+    exclude: &GlobSet,
+    files: &mut Vec<InputFile>,
+) {
+    for entry in walk_builder(dir, base, options, exclude).build().flatten() {
+        let path = entry.path();
+        if entry.file_type().is_some_and(|kind| kind.is_file()) && is_svg_path(path, options.svgz) {
+            files.push(InputFile {
+                path: path.to_path_buf(),
+                base: base.to_path_buf(),
+            });
+        }
+    }
+}
+
+fn walk_builder(dir: &Path, base: &Path, options: &InputOptions, exclude: &GlobSet) -> WalkBuilder {
+    let honor_ignore = !options.no_ignore;
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .hidden(false)
+        .parents(honor_ignore)
+        .ignore(honor_ignore)
+        .git_ignore(honor_ignore)
+        .git_global(honor_ignore)
+        .git_exclude(honor_ignore)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b));
+    if honor_ignore {
+        builder.add_custom_ignore_filename(IGNORE_FILE_NAME);
+    }
+
+    let filter_exclude = exclude.clone();
+    let filter_base = base.to_path_buf();
+    builder.filter_entry(move |entry| {
+        entry.depth() == 0 || !is_excluded(&filter_exclude, entry.path(), &filter_base)
+    });
+    builder
+}
+
+/// Matches `.svg` (and `.svgz` when enabled), ignoring case.
+pub fn is_svg_path(path: &Path, svgz: bool) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            ext.eq_ignore_ascii_case("svg") || (svgz && ext.eq_ignore_ascii_case("svgz"))
+        })
+}
+
+fn is_svgz_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svgz"))
+}
+
+/// Reads an SVG file, decompressing it when it is gzipped.
+pub fn read_svg(path: &Path) -> Result<String> {
+    let bytes = fs::read(path).map_err(|e| io_error(path, e))?;
+    let bytes = if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decoded = Vec::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_end(&mut decoded)
+            .map_err(|e| io_error(path, e))?;
+        decoded
+    } else {
+        bytes
+    };
+    String::from_utf8(bytes)
+        .map_err(|e| io_error(path, io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+/// Encodes `text` for `path`: gzipped for `.svgz`, plain UTF-8 otherwise.
+pub fn encode_svg(path: &Path, text: &str) -> Result<Vec<u8>> {
+    if !is_svgz_path(path) {
+        return Ok(text.as_bytes().to_vec());
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(text.as_bytes())
+        .map_err(|e| io_error(path, e))?;
+    encoder.finish().map_err(|e| io_error(path, e))
+}
+
+/// Replaces `path` with `text` by writing a temporary file next to it and
+/// renaming it over the original, so readers never see a partial file.
+pub fn replace_svg(path: &Path, text: &str) -> Result<()> {
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut temp = NamedTempFile::new_in(dir).map_err(|e| io_error(dir, e))?;
+    temp.write_all(&encode_svg(path, text)?)
+        .map_err(|e| io_error(temp.path(), e))?;
+    if let Ok(metadata) = fs::metadata(path) {
+        let _ = fs::set_permissions(temp.path(), metadata.permissions());
+    }
+    temp.persist(path).map_err(|e| io_error(path, e.error))?;
+    Ok(())
+}
+
+/// `icons/logo.svg` with suffix `.min.svg` becomes `icons/logo.min.svg`.
+pub fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or(path.as_os_str());
+    let mut name = stem.to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn write_svg(path: &Path, text: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| io_error(parent, e))?;
+    }
+    fs::write(path, encode_svg(path, text)?).map_err(|e| io_error(path, e))
+}
+
+fn invalid_input(path: &Path, message: impl Into<String>) -> Error {
+    io_error(
+        path,
+        io::Error::new(io::ErrorKind::InvalidInput, message.into()),
+    )
+}
+
+pub fn io_error(path: &Path, source: io::Error) -> Error {
+    Error::Io {
+        path: Some(path.to_path_buf()),
+        source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(root: &Path, relative: &str, contents: &[u8]) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn relatives(files: &[InputFile]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| file.relative().to_string_lossy().replace('\\', "/"))
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_inputs_filters_and_ignores() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "a.svg", b"<svg/>");
+        touch(root, "B.SVG", b"<svg/>");
+        touch(root, "c.svgz", b"");
+        touch(root, "notes.txt", b"");
+        touch(root, "vendor/d.svg", b"<svg/>");
+        touch(root, "build/e.svg", b"<svg/>");
+        touch(root, ".gitignore", b"build/\n");
+
+        let options = InputOptions {
+            exclude: vec!["vendor".to_string()],
+            ..InputOptions::default()
+        };
+        let files = collect_inputs(&[root.to_path_buf()], &options).unwrap();
+        assert_eq!(relatives(&files), ["B.SVG", "a.svg"]);
+
+        let options = InputOptions {
+            svgz: true,
+            no_ignore: true,
+            ..InputOptions::default()
+        };
+        let files = collect_inputs(&[root.to_path_buf()], &options).unwrap();
+        assert_eq!(
+            relatives(&files),
+            ["B.SVG", "a.svg", "build/e.svg", "c.svgz", "vendor/d.svg"]
+        );
+    }
+
+    #[test]
+    fn test_collect_inputs_expands_globs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "icons/a.svg", b"<svg/>");
+        touch(root, "icons/sub/b.svg", b"<svg/>");
+        touch(root, "icons/sub/b.min.svg", b"<svg/>");
+
+        let pattern = root.join("icons/**/*.svg");
+        let options = InputOptions {
+            exclude: vec!["*.min.svg".to_string()],
+            ..InputOptions::default()
+        };
+        let files = collect_inputs(&[pattern.clone(), pattern], &options).unwrap();
+        assert_eq!(relatives(&files), ["a.svg", "sub/b.svg"]);
+
+        let missing = root.join("nothing/*.svg");
+        assert!(collect_inputs(&[missing], &InputOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_collect_inputs_filters_glob_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        touch(root, "icons/a.svg", b"<svg/>");
+        touch(root, "icons/vendor/b.svg", b"<svg/>");
+        touch(root, "icons/build/c.svg", b"<svg/>");
+        touch(root, "icons/d.svg", b"<svg/>");
+        touch(root, "icons/.svgtidyignore", b"build/\nd.svg\n");
+
+        let pattern = root.join("icons/**/*.svg");
+        let options = InputOptions {
+            exclude: vec!["vendor".to_string()],
+            ..InputOptions::default()
+        };
+        let files = collect_inputs(std::slice::from_ref(&pattern), &options).unwrap();
+        assert_eq!(relatives(&files), ["a.svg"]);
+
+        let options = InputOptions {
+            no_ignore: true,
+            ..options
+        };
+        let files = collect_inputs(&[pattern], &options).unwrap();
+        assert_eq!(relatives(&files), ["a.svg", "build/c.svg", "d.svg"]);
+    }
+
+    #[test]
+    fn test_replace_svg_and_suffix() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("icon.svg");
+        fs::write(&path, "<svg> </svg>").unwrap();
+        replace_svg(&path, "<svg/>").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<svg/>");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(
+            suffixed_path(Path::new("icons/logo.svgz"), ".min.svg"),
+            Path::new("icons/logo.min.svg")
+        );
+    }
+
+    #[test]
+    fn test_svgz_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("icon.svgz");
+        write_svg(&path, "<svg/>").unwrap();
+
+        assert!(fs::read(&path).unwrap().starts_with(&[0x1f, 0x8b]));
+        assert_eq!(read_svg(&path).unwrap(), "<svg/>");
+    }
+}
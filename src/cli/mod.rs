@@ -0,0 +1,8 @@
+//! Helpers for the `svgtidy` command-line tool.
+
+pub mod check;
+pub mod diff;
+pub mod explain;
+pub mod files;
+pub mod report;
+pub mod timings;
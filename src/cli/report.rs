@@ -0,0 +1,173 @@
+//! Size reports for the `svgtidy` command-line tool.
+
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Write;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+/// The outcome for one input file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileReport {
+    pub path: String,
+    pub original_size: usize,
+    pub optimized_size: usize,
+    pub saved_percent: f64,
+}
+
+impl FileReport {
+    pub fn new(path: &Path, original_size: usize, optimized_size: usize) -> Self {
+        Self {
+            path: path.display().to_string(),
+            original_size,
+            optimized_size,
+            saved_percent: saved_percent(original_size, optimized_size),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileError {
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Totals {
+    pub files: usize,
+    pub original_size: usize,
+    pub optimized_size: usize,
+    pub saved_percent: f64,
+    pub elapsed_ms: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Report {
+    pub files: Vec<FileReport>,
+    pub errors: Vec<FileError>,
+    pub total: Totals,
+}
+
+impl Report {
+    pub fn new(files: Vec<FileReport>, errors: Vec<FileError>, elapsed: Duration) -> Self {
+        let original_size = files.iter().map(|file| file.original_size).sum();
+        let optimized_size = files.iter().map(|file| file.optimized_size).sum();
+        let total = Totals {
+            files: files.len(),
+            original_size,
+            optimized_size,
+            saved_percent: saved_percent(original_size, optimized_size),
+            elapsed_ms: elapsed.as_secs_f64() * 1000.0,
+        };
+        Self {
+            files,
+            errors,
+            total,
+        }
+    }
+
+    /// One line per file followed by the totals.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for file in &self.files {
+            let _ = writeln!(
+                out,
+                "{}: {}",
+                file.path,
+                format_change(file.original_size, file.optimized_size)
+            );
+        }
+        let total = &self.total;
+        let _ = write!(
+            out,
+            "{} {}: {} in {:.0} ms",
+            total.files,
+            if total.files == 1 { "file" } else { "files" },
+            format_change(total.original_size, total.optimized_size),
+            total.elapsed_ms
+        );
+        if !self.errors.is_empty() {
+            let _ = write!(out, ", {} failed", self.errors.len());
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("report serializes to JSON")
+    }
+
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.to_text(),
+            ReportFormat::Json => self.to_json(),
+        }
+    }
+}
+
+pub fn saved_percent(original_size: usize, optimized_size: usize) -> f64 {
+    if original_size == 0 {
+        return 0.0;
+    }
+    let saved = (original_size as f64 - optimized_size as f64) / original_size as f64 * 100.0;
+    (saved * 10.0).round() / 10.0
+}
+
+/// `"1200 B -> 800 B (-33.3%)"`.
+pub fn format_change(original_size: usize, optimized_size: usize) -> String {
+    format!(
+        "{} B -> {} B ({:+.1}%)",
+        original_size,
+        optimized_size,
+        0.0 - saved_percent(original_size, optimized_size)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Report {
+        Report::new(
+            vec![
+                FileReport::new(Path::new("a.svg"), 1200, 800),
+                FileReport::new(Path::new("b.svg"), 300, 300),
+            ],
+            vec![FileError {
+                path: "c.svg".to_string(),
+                message: "parse error at 1:1: unexpected end of stream".to_string(),
+            }],
+            Duration::from_millis(12),
+        )
+    }
+
+    #[test]
+    fn test_text_report() {
+        assert_eq!(
+            sample().to_text(),
+            "a.svg: 1200 B -> 800 B (-33.3%)\nb.svg: 300 B -> 300 B (+0.0%)\n2 files: 1500 B -> 1100 B (-26.7%) in 12 ms, 1 failed"
+        );
+    }
+
+    #[test]
+    fn test_json_report() {
+        let json: serde_json::Value = serde_json::from_str(&sample().to_json()).unwrap();
+
+        assert_eq!(json["files"][0]["path"], "a.svg");
+        assert_eq!(json["files"][0]["optimizedSize"], 800);
+        assert_eq!(json["files"][0]["savedPercent"], 33.3);
+        assert_eq!(json["errors"][0]["path"], "c.svg");
+        assert_eq!(json["total"]["files"], 2);
+        assert_eq!(json["total"]["originalSize"], 1500);
+        assert_eq!(json["total"]["elapsedMs"], 12.0);
+    }
+}
@@ -0,0 +1,125 @@
+//! `--timings`: wall time per plugin, summed over every file in a run.
+
+use indexmap::IndexMap;
+use std::cmp::Reverse;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+use svgtidy::pipeline::PipelineObserver;
+use svgtidy::tree::Document;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PluginTiming {
+    pub runs: usize,
+    /// Times the plugin was enabled but not needed for the document.
+    pub skipped: usize,
+    pub total: Duration,
+}
+
+#[derive(Debug, Default)]
+pub struct Timings {
+    plugins: IndexMap<String, PluginTiming>,
+    started: Option<Instant>,
+}
+
+impl Timings {
+    pub fn merge(&mut self, other: Timings) {
+        for (name, timing) in other.plugins {
+            let entry = self.plugins.entry(name).or_default();
+            entry.runs += timing.runs;
+            entry.skipped += timing.skipped;
+            entry.total += timing.total;
+        }
+    }
+
+    /// A table of plugins, slowest first.
+    pub fn to_text(&self) -> String {
+        let mut rows: Vec<_> = self.plugins.iter().collect();
+        rows.sort_by_key(|(_, timing)| Reverse(timing.total));
+        let width = rows
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            .max("plugin".len());
+
+        let mut out = format!(
+            "{:<width$}  {:>6}  {:>7}  {:>10}  {:>10}\n",
+            "plugin", "runs", "skipped", "total", "average"
+        );
+        let mut total = Duration::ZERO;
+        for (name, timing) in rows {
+            total += timing.total;
+            let average = if timing.runs == 0 {
+                Duration::ZERO
+            } else {
+                timing.total / timing.runs as u32
+            };
+            let _ = writeln!(
+                out,
+                "{:<width$}  {:>6}  {:>7}  {:>10}  {:>10}",
+                name,
+                timing.runs,
+                timing.skipped,
+                format_ms(timing.total),
+                format_ms(average)
+            );
+        }
+        let _ = write!(
+            out,
+            "{:<width$}  {:>6}  {:>7}  {:>10}",
+            "total",
+            "",
+            "",
+            format_ms(total)
+        );
+        out
+    }
+}
+
+fn format_ms(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+impl PipelineObserver for Timings {
+    fn before_plugin(&mut self, _pass: usize, _name: &str, _doc: &Document) {
+        self.started = Some(Instant::now());
+    }
+
+    fn after_plugin(&mut self, _pass: usize, name: &str, _doc: &Document) {
+        let elapsed = self.started.take().map(|started| started.elapsed());
+        let entry = self.plugins.entry(name.to_string()).or_default();
+        entry.runs += 1;
+        entry.total += elapsed.unwrap_or_default();
+    }
+
+    fn skipped_plugin(&mut self, _pass: usize, name: &str) {
+        self.plugins.entry(name.to_string()).or_default().skipped += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use svgtidy::optimize_to_document_with_observer;
+    use svgtidy::pipeline::OptimizeOptions;
+
+    #[test]
+    fn test_timings_count_runs_and_skips() {
+        let options = OptimizeOptions::default();
+        let mut timings = Timings::default();
+        for _ in 0..2 {
+            let mut file = Timings::default();
+            optimize_to_document_with_observer("<svg><rect/></svg>", &options, &mut file).unwrap();
+            timings.merge(file);
+        }
+
+        assert_eq!(timings.plugins["removeComments"].runs, 2);
+        // No <style> element, so the stylesheet plugins are skipped.
+        let minify = timings.plugins["minifyStyles"];
+        assert_eq!((minify.runs, minify.skipped), (0, 2));
+
+        let text = timings.to_text();
+        assert!(text.starts_with("plugin"), "{text}");
+        assert!(text.lines().last().unwrap().starts_with("total"), "{text}");
+    }
+}
@@ -0,0 +1,243 @@
+//! Shared optimization profiles loaded from `svgtidy.config.toml` or
+//! `svgtidy.config.json`.
+//!
+//! ```toml
+//! precision = 2
+//!
+//! [plugins]
+//! removeTitle = false
+//! removeStyleElement = true
+//! cleanupNumericValues = { removePx = false }
+//! convertPathData = { enabled = true, floatPrecision = 4 }
+//! ```
+//!
+//! A plugin entry is either a boolean that toggles the plugin, or a table of
+//! parameters. A table only changes whether the plugin runs when it contains
+//! an `enabled` key. The top-level `order`, `multipass` and `maxPasses` keys
+//! map to the matching [`OptimizeOptions`] fields.
+
+use crate::error::{Error, Result};
+use crate::pipeline::{validate_options, OptimizeOptions, ParamValue, PluginParams};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File names looked up by [`find_config`], in priority order.
+pub const CONFIG_FILE_NAMES: &[&str] = &["svgtidy.config.toml", "svgtidy.config.json"];
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct Config {
+    pub precision: Option<usize>,
+    pub pretty: Option<bool>,
+    pub order: Option<Vec<String>>,
+    pub multipass: Option<bool>,
+    pub max_passes: Option<usize>,
+    #[serde(default)]
+    pub plugins: IndexMap<String, PluginSetting>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum PluginSetting {
+    Enabled(bool),
+    Params(PluginParams),
+}
+
+impl Config {
+    pub fn from_toml_str(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| Error::invalid_config(e.to_string().trim_end()))
+    }
+
+    pub fn from_json_str(text: &str) -> Result<Self> {
+        serde_json::from_str(text).map_err(|e| Error::invalid_config(e.to_string()))
+    }
+
+    /// Reads a config file, picking the format from its extension
+    /// (`.json` is JSON, anything else is TOML).
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+        let parsed = if path.extension().is_some_and(|ext| ext == "json") {
+            Self::from_json_str(&text)
+        } else {
+            Self::from_toml_str(&text)
+        };
+        parsed.map_err(|e| e.in_config(path))
+    }
+
+    /// Applies this config on top of `options`, then validates the plugin
+    /// names and parameters it references.
+    pub fn apply(&self, options: &mut OptimizeOptions) -> Result<()> {
+        if let Some(precision) = self.precision {
+            options.precision = precision;
+        }
+        if let Some(order) = &self.order {
+            options.order = Some(order.clone());
+        }
+        if let Some(multipass) = self.multipass {
+            options.multipass = multipass;
+        }
+        if let Some(max_passes) = self.max_passes {
+            options.max_passes = max_passes;
+        }
+
+        for (name, setting) in &self.plugins {
+            let enabled = match setting {
+                PluginSetting::Enabled(enabled) => Some(*enabled),
+                PluginSetting::Params(params) => {
+                    let mut params = params.clone();
+                    let enabled = match params.remove("enabled") {
+                        Some(ParamValue::Bool(enabled)) => Some(enabled),
+                        Some(other) => {
+                            return Err(Error::invalid_config(format!(
+                                "`enabled` of plugin `{name}` must be a boolean, got {other:?}"
+                            )))
+                        }
+                        None => None,
+                    };
+                    if !params.is_empty() {
+                        options.params.insert(name.clone(), params);
+                    }
+                    enabled
+                }
+            };
+
+            match enabled {
+                Some(true) => {
+                    options.disable.remove(name);
+                    options.enable.insert(name.clone());
+                }
+                Some(false) => {
+                    options.enable.remove(name);
+                    options.disable.insert(name.clone());
+                }
+                None => {}
+            }
+        }
+
+        validate_options(options)
+    }
+
+    pub fn to_options(&self) -> Result<OptimizeOptions> {
+        let mut options = OptimizeOptions::default();
+        self.apply(&mut options)?;
+        Ok(options)
+    }
+}
+
+/// Looks for a config file in `start` and each of its ancestors.
+pub fn find_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find_map(|dir| {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_config_toggles_and_params() {
+        let config = Config::from_toml_str(
+            r#"
+precision = 2
+
+[plugins]
+removeTitle = false
+mergePaths = true
+cleanupNumericValues = { removePx = false }
+convertPathData = { enabled = false, floatPrecision = 4 }
+"#,
+        )
+        .unwrap();
+        let options = config.to_options().unwrap();
+
+        assert_eq!(options.precision, 2);
+        assert!(options.disable.contains("removeTitle"));
+        assert!(options.disable.contains("convertPathData"));
+        assert!(options.enable.contains("mergePaths"));
+        assert_eq!(
+            options.params["cleanupNumericValues"].get_bool("removePx"),
+            Some(false)
+        );
+        assert_eq!(
+            options.params["convertPathData"].get_usize("floatPrecision"),
+            Some(4)
+        );
+        assert!(options.params["convertPathData"].get("enabled").is_none());
+    }
+
+    #[test]
+    fn test_config_order_and_multipass() {
+        let config = Config::from_toml_str(
+            "order = [\"collapseGroups\", \"convertPathData\"]\nmultipass = true\nmaxPasses = 4\n",
+        )
+        .unwrap();
+        let options = config.to_options().unwrap();
+
+        assert_eq!(
+            options.order,
+            Some(vec![
+                "collapseGroups".to_string(),
+                "convertPathData".to_string()
+            ])
+        );
+        assert!(options.multipass);
+        assert_eq!(options.max_passes, 4);
+    }
+
+    #[test]
+    fn test_json_config_matches_toml() {
+        let json = Config::from_json_str(
+            r#"{ "precision": 2, "plugins": { "removeTitle": false, "convertPathData": { "floatPrecision": 4 } } }"#,
+        )
+        .unwrap();
+        let toml = Config::from_toml_str(
+            "precision = 2\n[plugins]\nremoveTitle = false\nconvertPathData = { floatPrecision = 4 }\n",
+        )
+        .unwrap();
+
+        assert_eq!(json, toml);
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_plugin() {
+        let config = Config::from_toml_str("[plugins]\nnotAPlugin = true\n").unwrap();
+
+        assert!(matches!(
+            config.to_options(),
+            Err(Error::UnknownPlugin(names)) if names == ["notAPlugin"]
+        ));
+    }
+
+    #[test]
+    fn test_config_rejects_invalid_params() {
+        let config =
+            Config::from_toml_str("[plugins]\nconvertPathData = { floatPrecision = true }\n")
+                .unwrap();
+        assert!(config
+            .to_options()
+            .unwrap_err()
+            .to_string()
+            .contains("`floatPrecision` of plugin `convertPathData`"));
+
+        let config =
+            Config::from_toml_str("[plugins]\nremoveTitle = { keepFirst = true }\n").unwrap();
+        assert_eq!(
+            config.to_options().unwrap_err().to_string(),
+            "invalid config: unknown parameter `keepFirst` for plugin `removeTitle`"
+        );
+    }
+
+    #[test]
+    fn test_config_rejects_unknown_top_level_keys() {
+        assert!(matches!(
+            Config::from_toml_str("precison = 2\n"),
+            Err(Error::InvalidConfig { .. })
+        ));
+    }
+}
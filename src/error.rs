@@ -0,0 +1,125 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input is not well-formed XML. `line` and `column` are 1-based,
+    /// `offset` is the byte offset into the input.
+    Parse {
+        message: String,
+        line: u32,
+        column: u32,
+        offset: usize,
+    },
+    /// Plugin names that are neither built-in nor registered.
+    UnknownPlugin(Vec<String>),
+    Io {
+        path: Option<PathBuf>,
+        source: io::Error,
+    },
+    InvalidConfig {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    /// [`crate::registry::register_plugin`] refused the plugin `name`.
+    PluginRegistration {
+        name: String,
+        reason: RegistrationConflict,
+    },
+}
+
+/// Why a plugin could not be registered.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RegistrationConflict {
+    /// The name belongs to a built-in plugin.
+    Builtin,
+    /// Another plugin is already registered under the name.
+    AlreadyRegistered,
+    /// A `Before`/`After` position names this, which is not a built-in plugin.
+    UnknownAnchor(String),
+}
+
+impl Error {
+    pub(crate) fn io(path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Self::Io {
+            path: Some(path.into()),
+            source,
+        }
+    }
+
+    pub(crate) fn invalid_config(message: impl Into<String>) -> Self {
+        Self::InvalidConfig {
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    /// Attaches the config file path to an [`Error::InvalidConfig`].
+    pub(crate) fn in_config(self, config_path: &std::path::Path) -> Self {
+        match self {
+            Self::InvalidConfig {
+                path: None,
+                message,
+            } => Self::InvalidConfig {
+                path: Some(config_path.to_path_buf()),
+                message,
+            },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse {
+                message,
+                line,
+                column,
+                ..
+            } => write!(f, "parse error at {line}:{column}: {message}"),
+            Self::UnknownPlugin(names) => write!(f, "unknown plugin(s): {}", names.join(", ")),
+            Self::Io {
+                path: Some(path),
+                source,
+            } => write!(f, "{}: {source}", path.display()),
+            Self::Io { path: None, source } => write!(f, "{source}"),
+            Self::InvalidConfig {
+                path: Some(path),
+                message,
+            } => write!(f, "invalid config {}: {message}", path.display()),
+            Self::InvalidConfig {
+                path: None,
+                message,
+            } => write!(f, "invalid config: {message}"),
+            Self::PluginRegistration { name, reason } => match reason {
+                RegistrationConflict::Builtin => write!(f, "`{name}` is a built-in plugin"),
+                RegistrationConflict::AlreadyRegistered => {
+                    write!(f, "plugin `{name}` is already registered")
+                }
+                RegistrationConflict::UnknownAnchor(anchor) => write!(
+                    f,
+                    "cannot position `{name}` relative to unknown built-in plugin `{anchor}`"
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(source: io::Error) -> Self {
+        Self::Io { path: None, source }
+    }
+}
@@ -0,0 +1,182 @@
+//! C ABI for linking svgtidy from other languages. The header is generated
+//! into `include/svgtidy.h` by `scripts/build-header.sh`.
+//!
+//! Strings returned by svgtidy are owned by the caller and must be released
+//! with [`svgtidy_free`]. On failure a function returns `NULL` and
+//! [`svgtidy_last_error`] describes what went wrong.
+
+use crate::bindings::{optimize, Options};
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    // Error messages never contain NUL, but don't trust that blindly.
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Runs `f`, turning errors and panics into `NULL` plus a last error.
+fn guard(f: impl FnOnce() -> Result<CString, String>) -> *mut c_char {
+    clear_last_error();
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(output)) => output.into_raw(),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            set_last_error("svgtidy panicked".to_string());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+///
+/// See [`svgtidy_optimize`].
+unsafe fn optimize_buffer(
+    svg: *const u8,
+    svg_len: usize,
+    options_json: *const c_char,
+    out_len: *mut usize,
+) -> Result<CString, String> {
+    if svg.is_null() {
+        return Err("svg is NULL".to_string());
+    }
+    let bytes = std::slice::from_raw_parts(svg, svg_len);
+    let svg = std::str::from_utf8(bytes).map_err(|_| "svg is not valid UTF-8".to_string())?;
+
+    let options = if options_json.is_null() {
+        Options::default()
+    } else {
+        let json = CStr::from_ptr(options_json)
+            .to_str()
+            .map_err(|_| "options are not valid UTF-8".to_string())?;
+        Options::from_json(json).map_err(|e| e.to_string())?
+    };
+
+    let output = optimize(svg, &options).map_err(|e| e.to_string())?;
+    let output = CString::new(output).map_err(|_| "output contains a NUL byte".to_string())?;
+    if !out_len.is_null() {
+        *out_len = output.as_bytes().len();
+    }
+    Ok(output)
+}
+
+/// Optimizes the `svg_len` bytes of UTF-8 at `svg`.
+///
+/// `options_json` is `NULL` for the defaults, or a NUL-terminated JSON object
+/// in the `svgtidy.config.json` format plus `enable`/`disable` lists, e.g.
+/// `{"precision": 2, "disable": ["removeTitle"]}`.
+///
+/// Returns a NUL-terminated string to release with [`svgtidy_free`], and
+/// stores its length (without the NUL) in `out_len` unless that is `NULL`.
+/// Returns `NULL` on error, leaving `out_len` untouched.
+///
+/// # Safety
+///
+/// `svg` must point to `svg_len` readable bytes, `options_json` must be
+/// `NULL` or a NUL-terminated string, and `out_len` must be `NULL` or
+/// writable.
+#[no_mangle]
+pub unsafe extern "C" fn svgtidy_optimize(
+    svg: *const u8,
+    svg_len: usize,
+    options_json: *const c_char,
+    out_len: *mut usize,
+) -> *mut c_char {
+    guard(|| optimize_buffer(svg, svg_len, options_json, out_len))
+}
+
+/// Releases a string returned by svgtidy. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `ptr` must be `NULL` or a pointer returned by svgtidy that has not been
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn svgtidy_free(ptr: *mut c_char) {
+    if !ptr.is_null() {
+        drop(CString::from_raw(ptr));
+    }
+}
+
+/// Describes the last error on the calling thread, or returns `NULL` if the
+/// last call succeeded. The string stays valid until the next svgtidy call
+/// on the same thread and must not be freed.
+#[no_mangle]
+pub extern "C" fn svgtidy_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn optimize_c(svg: &str, options: Option<&str>) -> Result<String, String> {
+        let options = options.map(|options| CString::new(options).unwrap());
+        let options_ptr = options
+            .as_ref()
+            .map_or(ptr::null(), |options| options.as_ptr());
+        let mut len = 0;
+        unsafe {
+            let output = svgtidy_optimize(svg.as_ptr(), svg.len(), options_ptr, &mut len);
+            if output.is_null() {
+                let error = CStr::from_ptr(svgtidy_last_error());
+                return Err(error.to_str().unwrap().to_string());
+            }
+            assert!(svgtidy_last_error().is_null());
+            let text = CStr::from_ptr(output).to_str().unwrap().to_string();
+            assert_eq!(text.len(), len);
+            svgtidy_free(output);
+            Ok(text)
+        }
+    }
+
+    #[test]
+    fn test_optimize_with_options() {
+        assert_eq!(
+            optimize_c("<svg><title>t</title><rect/></svg>", None),
+            Ok("<svg><rect/></svg>".to_string())
+        );
+        assert_eq!(
+            optimize_c(
+                "<svg><title>t</title><rect/></svg>",
+                Some(r#"{"disable": ["removeTitle"]}"#)
+            ),
+            Ok("<svg><title>t</title><rect/></svg>".to_string())
+        );
+    }
+
+    #[test]
+    fn test_errors_are_reported_through_last_error() {
+        assert_eq!(
+            optimize_c("<svg>", None),
+            Err("parse error at 1:6: unclosed element <svg>".to_string())
+        );
+        assert_eq!(
+            optimize_c("<svg/>", Some(r#"{"enable": ["noSuchPlugin"]}"#)),
+            Err("unknown plugin(s): noSuchPlugin".to_string())
+        );
+        assert_eq!(
+            unsafe { svgtidy_optimize(ptr::null(), 0, ptr::null(), ptr::null_mut()) },
+            ptr::null_mut()
+        );
+        let error = unsafe { CStr::from_ptr(svgtidy_last_error()) };
+        assert_eq!(error.to_str().unwrap(), "svg is NULL");
+    }
+}
@@ -1,15 +1,28 @@
+pub mod bindings;
+pub mod config;
+pub mod error;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ffi;
 pub mod parser;
 pub mod pipeline;
 pub mod plugins;
 pub mod printer;
+pub mod registry;
+pub mod trace;
 pub mod tree;
 pub mod visitor;
+mod wasm;
 
-use crate::pipeline::{apply_default_pipeline, OptimizeOptions};
+pub use crate::error::{Error, Result};
+
+use crate::pipeline::{
+    apply_pipeline_with_observer, validate_options, OptimizeOptions, PipelineObserver,
+};
 use crate::tree::Document;
 use wasm_bindgen::prelude::*;
 
-#[wasm_bindgen]
+/// Optimizes `svg` with the default options. Input that cannot be parsed is
+/// returned unchanged; use [`optimize_with_options`] to get the error instead.
 pub fn optimize(svg: &str) -> String {
     match optimize_with_options(svg, &OptimizeOptions::default()) {
         Ok(output) => output,
@@ -17,13 +30,29 @@ pub fn optimize(svg: &str) -> String {
     }
 }
 
-pub fn optimize_with_options(svg: &str, options: &OptimizeOptions) -> Result<String, String> {
+/// JavaScript entry point. Unlike [`optimize`], this throws on invalid input.
+#[wasm_bindgen(js_name = optimize)]
+pub fn optimize_js(svg: &str) -> std::result::Result<String, JsError> {
+    Ok(optimize_with_options(svg, &OptimizeOptions::default())?)
+}
+
+pub fn optimize_with_options(svg: &str, options: &OptimizeOptions) -> Result<String> {
     let doc = optimize_to_document(svg, options)?;
     Ok(printer::print(&doc))
 }
 
-pub fn optimize_to_document(svg: &str, options: &OptimizeOptions) -> Result<Document, String> {
+pub fn optimize_to_document(svg: &str, options: &OptimizeOptions) -> Result<Document> {
+    optimize_to_document_with_observer(svg, options, &mut ())
+}
+
+/// Like [`optimize_to_document`], reporting each plugin run to `observer`.
+pub fn optimize_to_document_with_observer(
+    svg: &str,
+    options: &OptimizeOptions,
+    observer: &mut dyn PipelineObserver,
+) -> Result<Document> {
+    validate_options(options)?;
     let mut doc = parser::parse(svg)?;
-    apply_default_pipeline(&mut doc, options);
+    apply_pipeline_with_observer(&mut doc, options, observer)?;
     Ok(doc)
 }
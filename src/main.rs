@@ -1,25 +1,53 @@
+mod cli;
+
 use clap::Parser;
+use cli::check::{format_check, needs_optimization};
+use cli::diff::pretty_diff;
+use cli::explain::format_trace;
+use cli::files::{
+    collect_inputs, is_glob, read_svg, replace_svg, suffixed_path, write_svg, InputFile,
+    InputOptions,
+};
+use cli::report::{FileError, FileReport, Report, ReportFormat};
+use cli::timings::Timings;
 use rayon::prelude::*;
-use std::fs;
-use std::path::PathBuf;
-use svgtidy::optimize_to_document;
-use svgtidy::pipeline::{unknown_plugin_names, OptimizeOptions};
-use svgtidy::printer;
-use walkdir::WalkDir;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+use svgtidy::config::{find_config, Config};
+use svgtidy::pipeline::{validate_options, OptimizeOptions};
+use svgtidy::trace::optimize_to_document_with_trace;
+use svgtidy::tree::Document;
+use svgtidy::{optimize_to_document_with_observer, printer, Result};
 
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    /// Input file or directory
-    input: PathBuf,
+    /// Input files, directories or glob patterns (`-` reads from stdin)
+    #[arg(required_unless_present = "string")]
+    input: Vec<PathBuf>,
+
+    /// Optimize this SVG text instead of reading a file
+    #[arg(short, long, conflicts_with = "input")]
+    string: Option<String>,
 
-    /// Output file or directory (optional)
+    /// Output file or directory (optional, `-` writes to stdout)
     #[arg(short, long)]
     output: Option<PathBuf>,
 
-    /// Set numeric precision
-    #[arg(short, long, default_value_t = 3)]
-    precision: u8,
+    /// Overwrite the input files
+    #[arg(short, long, conflicts_with_all = ["output", "suffix", "string"])]
+    in_place: bool,
+
+    /// Write each result next to its input, replacing the extension with SUFFIX (e.g. `.min.svg`)
+    #[arg(long, conflicts_with_all = ["output", "string"])]
+    suffix: Option<String>,
+
+    /// Set numeric precision, at most 8 [default: 3]
+    #[arg(short, long)]
+    precision: Option<u8>,
 
     /// Enable specific plugins (comma-separated list)
     #[arg(long, value_delimiter = ',')]
@@ -29,28 +57,147 @@ struct Args {
     #[arg(long, value_delimiter = ',')]
     disable: Vec<String>,
 
-    /// Pretty print output (disable minification)
+    /// Run exactly these plugins in this order (comma-separated list)
+    #[arg(long, value_delimiter = ',')]
+    order: Option<Vec<String>>,
+
+    /// Re-run the pipeline until the output stops shrinking; `--multipass=false` overrides the config file
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    multipass: Option<bool>,
+
+    /// Maximum number of passes in multipass mode [default: 10]
     #[arg(long)]
-    pretty: bool,
+    max_passes: Option<usize>,
+
+    /// Skip paths matching these glob patterns, relative to each input (comma-separated list)
+    #[arg(long, value_delimiter = ',')]
+    exclude: Vec<String>,
+
+    /// Also process gzip-compressed .svgz files in directories
+    #[arg(long)]
+    svgz: bool,
+
+    /// Don't honor .gitignore, .ignore and .svgtidyignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Don't write anything; exit with an error if any file would change
+    #[arg(long, conflicts_with_all = ["output", "in_place", "suffix"])]
+    check: bool,
+
+    /// Print a unified diff of the pretty-printed input and output instead of writing files
+    #[arg(long, conflicts_with_all = ["output", "in_place", "suffix"])]
+    diff: bool,
+
+    /// Print which plugin changed what, and how many bytes each saved, instead of writing files
+    #[arg(long, conflicts_with_all = ["output", "in_place", "suffix"])]
+    explain: bool,
+
+    /// Print the time spent in each plugin, summed over all files, to stderr
+    #[arg(long, conflicts_with = "explain")]
+    timings: bool,
+
+    /// With --check, only fail files that would shrink by more than PERCENT
+    #[arg(long, value_name = "PERCENT", requires = "check")]
+    threshold: Option<f64>,
+
+    /// Print original vs. optimized sizes [default: text for batch runs]
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    report: Option<ReportFormat>,
+
+    /// Pretty print output (disable minification); `--pretty=false` overrides the config file
+    #[arg(long, value_name = "BOOL", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pretty: Option<bool>,
+
+    /// Config file (default: nearest svgtidy.config.toml or svgtidy.config.json)
+    #[arg(short, long, conflicts_with = "no_config")]
+    config: Option<PathBuf>,
+
+    /// Ignore any svgtidy.config.toml / svgtidy.config.json
+    #[arg(long)]
+    no_config: bool,
+}
+
+impl Args {
+    /// Whether to pretty print, once `pretty` holds the flag or the config
+    /// file setting.
+    fn pretty(&self) -> bool {
+        self.pretty.unwrap_or(false)
+    }
+}
+
+fn load_config(args: &Args) -> Result<Config> {
+    if let Some(path) = &args.config {
+        return Config::load(path);
+    }
+    if args.no_config {
+        return Ok(Config::default());
+    }
+
+    let cwd = std::env::current_dir()?;
+    match find_config(&cwd) {
+        Some(path) => Config::load(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+fn build_options(args: &Args, config: &Config) -> Result<OptimizeOptions> {
+    let mut options = config.to_options()?;
+
+    // Command-line flags take precedence over the config file.
+    if let Some(precision) = args.precision {
+        options.precision = precision as usize;
+    }
+    if let Some(order) = &args.order {
+        options.order = Some(order.clone());
+    }
+    if let Some(multipass) = args.multipass {
+        options.multipass = multipass;
+    }
+    if let Some(max_passes) = args.max_passes {
+        options.max_passes = max_passes;
+    }
+    for name in &args.enable {
+        options.disable.remove(name);
+        options.enable.insert(name.clone());
+    }
+    for name in &args.disable {
+        options.enable.remove(name);
+        options.disable.insert(name.clone());
+    }
+
+    validate_options(&options)?;
+    Ok(options)
 }
 
-fn build_options(args: &Args) -> Result<OptimizeOptions, String> {
-    let options = OptimizeOptions {
-        precision: args.precision as usize,
-        enable: args.enable.iter().cloned().collect(),
-        disable: args.disable.iter().cloned().collect(),
+/// Optimizes `text`, adding plugin run times to `timings` when given.
+fn optimize_document(
+    text: &str,
+    options: &OptimizeOptions,
+    timings: Option<&Mutex<Timings>>,
+) -> Result<Document> {
+    let Some(timings) = timings else {
+        return optimize_to_document_with_observer(text, options, &mut ());
     };
+    let mut file_timings = Timings::default();
+    let doc = optimize_to_document_with_observer(text, options, &mut file_timings)?;
+    timings.lock().unwrap().merge(file_timings);
+    Ok(doc)
+}
 
-    let unknown = unknown_plugin_names(&options);
-    if unknown.is_empty() {
-        Ok(options)
-    } else {
-        Err(format!("Unknown plugin(s): {}", unknown.join(", ")))
+fn print_timings(timings: Option<Mutex<Timings>>) {
+    if let Some(timings) = timings {
+        eprintln!("{}", timings.into_inner().unwrap().to_text());
     }
 }
 
-fn process_string(text: &str, options: &OptimizeOptions, pretty: bool) -> Result<String, String> {
-    let doc = optimize_to_document(text, options).map_err(|e| format!("Parse error: {}", e))?;
+fn process_string(
+    text: &str,
+    options: &OptimizeOptions,
+    pretty: bool,
+    timings: Option<&Mutex<Timings>>,
+) -> Result<String> {
+    let doc = optimize_document(text, options, timings)?;
     if pretty {
         Ok(printer::print_pretty(&doc))
     } else {
@@ -58,83 +205,306 @@ fn process_string(text: &str, options: &OptimizeOptions, pretty: bool) -> Result
     }
 }
 
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn read_input(path: &Path) -> Result<String> {
+    if is_stdio(path) {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        Ok(text)
+    } else {
+        read_svg(path)
+    }
+}
+
+fn write_output(path: Option<&Path>, out: &str) -> Result<()> {
+    match path {
+        Some(path) if !is_stdio(path) => write_svg(path, out),
+        _ => {
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "{}", out)?;
+            stdout.flush()?;
+            Ok(())
+        }
+    }
+}
+
+/// Where optimized files go.
+enum Output<'a> {
+    /// `-o <DIR>`, mirroring each file below its input root.
+    Dir(&'a Path),
+    InPlace,
+    Suffix(&'a str),
+}
+
+impl Output<'_> {
+    fn from_args(args: &Args) -> Option<Output<'_>> {
+        if args.in_place {
+            Some(Output::InPlace)
+        } else if let Some(suffix) = &args.suffix {
+            Some(Output::Suffix(suffix))
+        } else {
+            args.output.as_deref().map(Output::Dir)
+        }
+    }
+
+    fn path_for(&self, input: &Path, relative: &Path) -> PathBuf {
+        match self {
+            Output::Dir(dir) => dir.join(relative),
+            Output::InPlace => input.to_path_buf(),
+            Output::Suffix(suffix) => suffixed_path(input, suffix),
+        }
+    }
+
+    fn write(&self, file: &InputFile, out: &str) -> Result<()> {
+        let path = self.path_for(&file.path, file.relative());
+        match self {
+            Output::InPlace => replace_svg(&path, out),
+            _ => write_svg(&path, out),
+        }
+    }
+}
+
 fn main() {
-    let args = Args::parse();
-    let options = match build_options(&args) {
+    let mut args = Args::parse();
+    let options = match load_config(&args).and_then(|config| {
+        args.pretty = args.pretty.or(config.pretty);
+        build_options(&args, &config)
+    }) {
         Ok(options) => options,
-        Err(error) => {
-            eprintln!("Error: {}", error);
-            std::process::exit(1);
+        Err(error) => exit_with_error(error),
+    };
+
+    let single_input = match args.input.as_slice() {
+        [] => Some(PathBuf::from("-")),
+        [input] if is_stdio(input) || (input.is_file() && !is_glob(input)) => Some(input.clone()),
+        inputs => {
+            if inputs.iter().any(|input| is_stdio(input)) {
+                exit_with_error("`-` (stdin) can only be used as the only input");
+            }
+            None
         }
     };
 
-    if args.input.is_dir() {
-        // Batch Mode
-        let walker = WalkDir::new(&args.input).into_iter();
-
-        // Collect files first to parallelize
-        let files: Vec<PathBuf> = walker
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "svg"))
-            .map(|e| e.path().to_owned())
-            .collect();
-
-        println!("Found {} SVG files. Processing in parallel...", files.len());
-
-        files.par_iter().for_each(|input_path| {
-            // Calculate output path
-            let output_path = if let Some(ref out_dir) = args.output {
-                // Mirror structure: out_dir + (input_path - args.input)
-                let relative = input_path.strip_prefix(&args.input).unwrap();
-                Some(out_dir.join(relative))
+    let output = Output::from_args(&args);
+    let started = Instant::now();
+    let timings = args.timings.then(|| Mutex::new(Timings::default()));
+
+    if args.check || args.diff || args.explain {
+        let files = match single_input {
+            Some(path) => vec![InputFile {
+                path,
+                base: PathBuf::new(),
+            }],
+            None => collect_batch_inputs(&args),
+        };
+        run_preview(&args, &options, &files, timings);
+    }
+
+    if let Some(input) = single_input {
+        // Single File Mode (also stdin and --string)
+        if is_stdio(&input) && matches!(output, Some(Output::InPlace | Output::Suffix(_))) {
+            exit_with_error("--in-place and --suffix need input files");
+        }
+        let text = match &args.string {
+            Some(text) => Ok(text.clone()),
+            None => read_input(&input),
+        };
+        let file = InputFile {
+            path: input,
+            base: PathBuf::new(),
+        };
+        let result = text.and_then(|text| {
+            let out = process_string(&text, &options, args.pretty(), timings.as_ref())?;
+            match &output {
+                Some(Output::Dir(_)) | None => write_output(args.output.as_deref(), &out)?,
+                Some(output) => output.write(&file, &out)?,
+            }
+            Ok((text.len(), out.len()))
+        });
+        let (original_size, optimized_size) = result.unwrap_or_else(|e| exit_with_error(e));
+
+        // Reports go to stderr, as in batch mode, so they never mix with
+        // SVG on stdout.
+        let writes_file = args.output.as_deref().is_some_and(|path| !is_stdio(path))
+            || args.in_place
+            || args.suffix.is_some();
+        let format = args.report.or(writes_file.then_some(ReportFormat::Text));
+        if let Some(format) = format {
+            let name = if args.string.is_some() {
+                Path::new("<string>")
+            } else if is_stdio(&file.path) {
+                Path::new("<stdin>")
             } else {
-                None // If no output dir, maybe print? Or overwrite? Let's safeguard and strictly require output dir for batch OR just print (too noisy).
-                     // Safest: require output dir for batch for now, or just don't write.
+                &file.path
             };
+            let report = Report::new(
+                vec![FileReport::new(name, original_size, optimized_size)],
+                Vec::new(),
+                started.elapsed(),
+            );
+            eprintln!("{}", report.render(format));
+        }
+        print_timings(timings);
+        return;
+    }
+
+    // Batch Mode
+    let Some(output) = output else {
+        exit_with_error(
+            "batch mode needs --output <DIR>, --in-place or --suffix <SUFFIX> to say where results go",
+        );
+    };
+    let files = collect_batch_inputs(&args);
+    if let Err(error) = check_destinations(&output, &files) {
+        exit_with_error(error);
+    }
 
-            if let Ok(text) = fs::read_to_string(input_path) {
-                match process_string(&text, &options, args.pretty) {
-                    Ok(out) => {
-                        if let Some(path) = output_path {
-                            // Ensure parent exists
-                            if let Some(parent) = path.parent() {
-                                let _ = fs::create_dir_all(parent);
-                            }
-                            if let Err(e) = fs::write(&path, out) {
-                                eprintln!("Error writing {:?}: {}", path, e);
-                            } else {
-                                // Success silent?
-                            }
-                        } else {
-                            // If no output, just print summary? Or dry run?
-                        }
-                    }
-                    Err(e) => eprintln!("Error processing {:?}: {}", input_path, e),
-                }
+    let results: Vec<Result<FileReport>> = files
+        .par_iter()
+        .map(|file| {
+            let text = read_svg(&file.path)?;
+            let out = process_string(&text, &options, args.pretty(), timings.as_ref())?;
+            output.write(file, &out)?;
+            Ok(FileReport::new(&file.path, text.len(), out.len()))
+        })
+        .collect();
+
+    let mut reports = Vec::new();
+    let mut errors = Vec::new();
+    for (file, result) in files.iter().zip(results) {
+        match result {
+            Ok(report) => reports.push(report),
+            Err(e) => {
+                eprintln!("Error processing {:?}: {}", file.path, e);
+                errors.push(FileError {
+                    path: file.path.display().to_string(),
+                    message: e.to_string(),
+                });
             }
-        });
+        }
+    }
 
-        println!("Done.");
-    } else {
-        // Single File Mode
-        match fs::read_to_string(&args.input) {
-            Ok(text) => match process_string(&text, &options, args.pretty) {
-                Ok(out) => {
-                    if let Some(output_path) = args.output {
-                        fs::write(output_path, out).expect("Could not write output file");
-                    } else {
-                        println!("{}", out);
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error: {}", e);
-                    std::process::exit(1);
-                }
-            },
+    let failed = !errors.is_empty();
+    let report = Report::new(reports, errors, started.elapsed());
+    eprintln!(
+        "{}",
+        report.render(args.report.unwrap_or(ReportFormat::Text))
+    );
+    print_timings(timings);
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// Refuses to run when two inputs would be written to the same file, e.g.
+/// `a/icon.svg` and `b/icon.svg` given explicitly with `-o DIR`.
+fn check_destinations(output: &Output, files: &[InputFile]) -> Result<(), String> {
+    let mut sources: HashMap<PathBuf, &Path> = HashMap::new();
+    for file in files {
+        let destination = output.path_for(&file.path, file.relative());
+        if let Some(previous) = sources.insert(destination.clone(), &file.path) {
+            return Err(format!(
+                "{} and {} would both be written to {}",
+                previous.display(),
+                file.path.display(),
+                destination.display()
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn collect_batch_inputs(args: &Args) -> Vec<InputFile> {
+    let mut exclude = args.exclude.clone();
+    if let Some(suffix) = &args.suffix {
+        // Don't re-process earlier results.
+        exclude.push(format!("*{suffix}"));
+    }
+    let input_options = InputOptions {
+        exclude,
+        svgz: args.svgz,
+        no_ignore: args.no_ignore,
+    };
+    collect_inputs(&args.input, &input_options).unwrap_or_else(|e| exit_with_error(e))
+}
+
+/// `--check`, `--diff` and `--explain`: optimizes every file in memory and
+/// reports on it. Nothing is written.
+fn run_preview(
+    args: &Args,
+    options: &OptimizeOptions,
+    files: &[InputFile],
+    timings: Option<Mutex<Timings>>,
+) -> ! {
+    let text_input = args.string.as_ref();
+    let results: Vec<Result<(Option<FileReport>, String)>> = files
+        .par_iter()
+        .map(|file| {
+            let text = match text_input {
+                Some(text) => text.clone(),
+                None => read_input(&file.path)?,
+            };
+            let (doc, trace) = if args.explain {
+                let (doc, trace) = optimize_to_document_with_trace(&text, options)?;
+                (doc, Some(trace))
+            } else {
+                (optimize_document(&text, options, timings.as_ref())?, None)
+            };
+            let out = if args.pretty() {
+                printer::print_pretty(&doc)
+            } else {
+                printer::print(&doc)
+            };
+            let name = if text_input.is_some() {
+                Path::new("<string>")
+            } else if is_stdio(&file.path) {
+                Path::new("<stdin>")
+            } else {
+                &file.path
+            };
+            let name_str = name.display().to_string();
+            let mut details = String::new();
+            if let Some(trace) = &trace {
+                details.push_str(&format_trace(&name_str, trace));
+            }
+            if args.diff {
+                details.push_str(&pretty_diff(&text, &doc, &name_str)?);
+            }
+            let failure = needs_optimization(&text, &out, args.threshold)
+                .then(|| FileReport::new(name, text.len(), out.len()));
+            Ok((failure, details))
+        })
+        .collect();
+
+    let mut failures = Vec::new();
+    let mut failed = false;
+    let mut stdout = io::stdout().lock();
+    for (file, result) in files.iter().zip(results) {
+        match result {
+            Ok((failure, details)) => {
+                let _ = stdout.write_all(details.as_bytes());
+                failures.extend(failure);
+            }
             Err(e) => {
-                eprintln!("Could not read input file: {}", e);
-                std::process::exit(1);
+                failed = true;
+                eprintln!("Error processing {:?}: {}", file.path, e);
             }
         }
     }
+
+    if args.check {
+        let _ = writeln!(stdout, "{}", format_check(&failures, files.len()));
+        failed |= !failures.is_empty();
+    }
+    let _ = stdout.flush();
+    print_timings(timings);
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+fn exit_with_error(error: impl std::fmt::Display) -> ! {
+    eprintln!("Error: {}", error);
+    std::process::exit(1);
 }
@@ -1,28 +1,47 @@
-use crate::tree::{Document, Element, Node};
-use xmlparser::{Token, Tokenizer};
+use crate::error::{Error, Result};
+use crate::tree::{Document, Element, Node, SourceSpan, ENTITY_REF_END, ENTITY_REF_START};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+use xmlparser::{EntityDefinition, StrSpan, Token, Tokenizer};
 
-pub fn parse(text: &str) -> Result<Document, String> {
+/// Nesting limit for entities that reference other entities.
+const MAX_ENTITY_DEPTH: usize = 8;
+
+/// Upper bound on the bytes a document may produce through entity
+/// expansion, so a handful of nested declarations can't blow up memory.
+const MAX_ENTITY_EXPANSION: usize = 1 << 20;
+
+pub fn parse(text: &str) -> Result<Document> {
     let mut doc = Document::new();
     let mut element_stack: Vec<Element> = Vec::new();
     let mut dtd_start: Option<usize> = None;
+    let mut entities: HashMap<&str, &str> = HashMap::new();
+    let mut expansion_budget = MAX_ENTITY_EXPANSION;
+    let mut locator = Locator::new(text);
 
     for token in Tokenizer::from(text) {
-        let token = token.map_err(|e| e.to_string())?;
+        let token = token.map_err(|e| xml_error(text, &e))?;
         match token {
-            Token::ElementStart { prefix, local, .. } => {
+            Token::ElementStart {
+                prefix,
+                local,
+                span,
+            } => {
                 let name = if prefix.is_empty() {
                     local.as_str().to_string()
                 } else {
                     format!("{}:{}", prefix.as_str(), local.as_str())
                 };
-                let element = Element::new(name);
+                let mut element = Element::new(name);
+                element.span = Some(locator.span(span.start(), span.end()));
                 element_stack.push(element);
             }
             Token::Attribute {
                 prefix,
                 local,
                 value,
-                ..
+                span,
             } => {
                 if let Some(current) = element_stack.last_mut() {
                     let key = if prefix.is_empty() {
@@ -30,27 +49,61 @@ pub fn parse(text: &str) -> Result<Document, String> {
                     } else {
                         format!("{}:{}", prefix.as_str(), local.as_str())
                     };
-                    current.attributes.insert(key, value.as_str().to_string());
+                    let value = decode(text, value, &entities, &mut expansion_budget)?;
+                    current
+                        .attribute_spans
+                        .insert(key.clone(), locator.span(span.start(), span.end()));
+                    current.attributes.insert(key, value);
                 }
             }
-            Token::ElementEnd { end, .. } => {
-                match end {
-                    xmlparser::ElementEnd::Open => {
-                        // Just finished attributes, nothing to do
+            Token::ElementEnd { end, span } => match end {
+                xmlparser::ElementEnd::Open => {
+                    bind_namespaces(&mut element_stack);
+                }
+                xmlparser::ElementEnd::Close(prefix, local) => {
+                    let name = if prefix.is_empty() {
+                        local.as_str().to_string()
+                    } else {
+                        format!("{}:{}", prefix.as_str(), local.as_str())
+                    };
+                    let Some(mut element) = element_stack.pop() else {
+                        return Err(parse_error(
+                            text,
+                            local.start(),
+                            format!("unexpected closing tag </{name}>"),
+                        ));
+                    };
+                    if element.name != name {
+                        return Err(parse_error(
+                            text,
+                            local.start(),
+                            format!(
+                                "mismatched closing tag </{name}>, expected </{}>",
+                                element.name
+                            ),
+                        ));
                     }
-                    xmlparser::ElementEnd::Close(..) | xmlparser::ElementEnd::Empty => {
-                        if let Some(element) = element_stack.pop() {
-                            if let Some(parent) = element_stack.last_mut() {
-                                parent.children.push(Node::Element(element));
-                            } else {
-                                doc.root.push(Node::Element(element));
-                            }
+                    close_span(&mut element, span.end());
+                    if let Some(parent) = element_stack.last_mut() {
+                        parent.children.push(Node::Element(element));
+                    } else {
+                        doc.root.push(Node::Element(element));
+                    }
+                }
+                xmlparser::ElementEnd::Empty => {
+                    bind_namespaces(&mut element_stack);
+                    if let Some(mut element) = element_stack.pop() {
+                        close_span(&mut element, span.end());
+                        if let Some(parent) = element_stack.last_mut() {
+                            parent.children.push(Node::Element(element));
+                        } else {
+                            doc.root.push(Node::Element(element));
                         }
                     }
                 }
-            }
-            Token::Text { text } => {
-                let content = text.as_str().to_string();
+            },
+            Token::Text { text: raw } => {
+                let content = decode(text, raw, &entities, &mut expansion_budget)?;
                 // Simple whitespace heuristic: if just whitespace, maybe ignore?
                 // For now, keep everything to be safe.
                 if let Some(current) = element_stack.last_mut() {
@@ -112,13 +165,273 @@ pub fn parse(text: &str) -> Result<Document, String> {
                     push_node(&mut doc, &mut element_stack, Node::Doctype(content));
                 }
             }
+            Token::EntityDeclaration {
+                name,
+                definition: EntityDefinition::EntityValue(value),
+                ..
+            } => {
+                // The first declaration of an entity is binding.
+                entities.entry(name.as_str()).or_insert(value.as_str());
+            }
             Token::EntityDeclaration { .. } => {}
         }
     }
 
+    if let Some(element) = element_stack.last() {
+        return Err(parse_error(
+            text,
+            text.len(),
+            format!("unclosed element <{}>", element.name),
+        ));
+    }
+
     Ok(doc)
 }
 
+fn close_span(element: &mut Element, end: usize) {
+    if let Some(span) = &mut element.span {
+        span.end = end;
+    }
+}
+
+/// Computes line/column for offsets that arrive mostly in increasing order,
+/// without rescanning the input from the start each time.
+struct Locator<'a> {
+    text: &'a str,
+    offset: usize,
+    line: u32,
+    column: u32,
+}
+
+impl<'a> Locator<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn span(&mut self, start: usize, end: usize) -> SourceSpan {
+        if start < self.offset {
+            *self = Self::new(self.text);
+        }
+        for c in self.text[self.offset..start].chars() {
+            if c == '\n' {
+                self.line += 1;
+                self.column = 1;
+            } else {
+                self.column += 1;
+            }
+        }
+        self.offset = start;
+
+        SourceSpan {
+            start,
+            end,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+/// Sets the in-scope namespaces of the innermost element once its
+/// attributes are known, inheriting its parent's bindings.
+fn bind_namespaces(element_stack: &mut [Element]) {
+    let Some((current, ancestors)) = element_stack.split_last_mut() else {
+        return;
+    };
+    let mut namespaces = ancestors
+        .last()
+        .map(|parent| parent.namespaces.clone())
+        .unwrap_or_default();
+
+    for (key, value) in &current.attributes {
+        let prefix = match key.as_str() {
+            "xmlns" => "",
+            key => match key.strip_prefix("xmlns:") {
+                Some(prefix) => prefix,
+                None => continue,
+            },
+        };
+        Arc::make_mut(&mut namespaces).insert(prefix.to_string(), value.clone());
+    }
+
+    current.namespaces = namespaces;
+}
+
+/// Resolves character references, the predefined entities and entities
+/// declared in the internal DTD subset. Expanded entity text is kept as
+/// character data; markup inside entity values is not parsed.
+fn decode(
+    text: &str,
+    raw: StrSpan,
+    entities: &HashMap<&str, &str>,
+    budget: &mut usize,
+) -> Result<String> {
+    decode_str(raw.as_str(), entities, 0, budget)
+        .map(Cow::into_owned)
+        .map_err(|(index, message)| parse_error(text, raw.start() + index, message))
+}
+
+fn decode_str<'a>(
+    raw: &'a str,
+    entities: &HashMap<&str, &str>,
+    depth: usize,
+    budget: &mut usize,
+) -> std::result::Result<Cow<'a, str>, (usize, String)> {
+    if !raw.contains(['&', ENTITY_REF_START]) {
+        return Ok(Cow::Borrowed(raw));
+    }
+
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find(['&', ENTITY_REF_START]) {
+        out.push_str(&rest[..start]);
+        if let Some(after) = rest[start..].strip_prefix(ENTITY_REF_START) {
+            push_entity_ref(&mut out, "");
+            rest = after;
+            continue;
+        }
+        let offset = raw.len() - rest.len() + start;
+        let reference = &rest[start + 1..];
+        let Some(end) = reference.find(';') else {
+            return Err((offset, "unterminated entity reference".to_string()));
+        };
+        let name = &reference[..end];
+
+        match name {
+            "amp" => out.push('&'),
+            "lt" => out.push('<'),
+            "gt" => out.push('>'),
+            "quot" => out.push('"'),
+            "apos" => out.push('\''),
+            _ if name.starts_with('#') => {
+                let code = match name.strip_prefix("#x") {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => name[1..].parse::<u32>().ok(),
+                };
+                match code.and_then(char::from_u32).filter(|&c| is_xml_char(c)) {
+                    Some(ENTITY_REF_START) => push_entity_ref(&mut out, ""),
+                    Some(c) => out.push(c),
+                    None => return Err((offset, format!("invalid character reference &{name};"))),
+                }
+            }
+            _ => {
+                let Some(value) = entities.get(name) else {
+                    // Undeclared entities (e.g. `&nbsp;` from an HTML editor)
+                    // are kept for the printer to write back out.
+                    if !is_xml_name(name) {
+                        return Err((offset, format!("invalid entity reference &{name};")));
+                    }
+                    push_entity_ref(&mut out, name);
+                    rest = &reference[end + 1..];
+                    continue;
+                };
+                if depth >= MAX_ENTITY_DEPTH {
+                    return Err((offset, format!("entity &{name}; is nested too deeply")));
+                }
+                let expanded = decode_str(value, entities, depth + 1, budget)
+                    .map_err(|(_, message)| (offset, message))?;
+                *budget = budget.checked_sub(expanded.len()).ok_or_else(|| {
+                    (
+                        offset,
+                        format!("entity expansion exceeds {MAX_ENTITY_EXPANSION} bytes"),
+                    )
+                })?;
+                out.push_str(&expanded);
+            }
+        }
+
+        rest = &reference[end + 1..];
+    }
+    out.push_str(rest);
+
+    Ok(Cow::Owned(out))
+}
+
+/// Stores a reference to an undeclared entity, or a literal
+/// `ENTITY_REF_START` when `name` is empty.
+fn push_entity_ref(out: &mut String, name: &str) {
+    out.push(ENTITY_REF_START);
+    out.push_str(name);
+    out.push(ENTITY_REF_END);
+}
+
+/// The `Char` production of XML 1.0.
+fn is_xml_char(c: char) -> bool {
+    matches!(c, '\t' | '\n' | '\r' | '\u{20}'..='\u{D7FF}' | '\u{E000}'..='\u{FFFD}' | '\u{10000}'..)
+}
+
+/// Whether `name` is an XML `Name`, i.e. may appear in an entity reference.
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | ':' | '-' | '.' | '\u{B7}'))
+}
+
+fn xml_error(text: &str, error: &xmlparser::Error) -> Error {
+    use xmlparser::Error as X;
+
+    let message = match error {
+        X::InvalidDeclaration(cause, _) => format!("invalid XML declaration: {cause}"),
+        X::InvalidComment(cause, _) => format!("invalid comment: {cause}"),
+        X::InvalidPI(cause, _) => format!("invalid processing instruction: {cause}"),
+        X::InvalidDoctype(cause, _) => format!("invalid DTD: {cause}"),
+        X::InvalidEntity(cause, _) => format!("invalid DTD entity: {cause}"),
+        X::InvalidElement(cause, _) => format!("invalid element: {cause}"),
+        X::InvalidAttribute(cause, _) => format!("invalid attribute: {cause}"),
+        X::InvalidCdata(cause, _) => format!("invalid CDATA: {cause}"),
+        X::InvalidCharData(cause, _) => format!("invalid character data: {cause}"),
+        X::UnknownToken(_) => "unknown token".to_string(),
+    };
+    let pos = error.pos();
+
+    Error::Parse {
+        message,
+        line: pos.row,
+        column: pos.col,
+        offset: byte_offset(text, pos.row, pos.col),
+    }
+}
+
+fn parse_error(text: &str, offset: usize, message: String) -> Error {
+    let (line, column) = line_column(text, offset);
+    Error::Parse {
+        message,
+        line,
+        column,
+        offset,
+    }
+}
+
+/// Converts a 1-based line/column (in characters) to a byte offset.
+fn byte_offset(text: &str, line: u32, column: u32) -> usize {
+    let line_start = if line <= 1 {
+        0
+    } else {
+        text.match_indices('\n')
+            .nth(line as usize - 2)
+            .map_or(text.len(), |(index, _)| index + 1)
+    };
+    text[line_start..]
+        .char_indices()
+        .nth(column.saturating_sub(1) as usize)
+        .map_or(text.len(), |(index, _)| line_start + index)
+}
+
+/// Converts a byte offset to a 1-based line/column (in characters).
+fn line_column(text: &str, offset: usize) -> (u32, u32) {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    let line = before.matches('\n').count() + 1;
+    let column = before[line_start..].chars().count() + 1;
+    (line as u32, column as u32)
+}
+
 fn push_node(doc: &mut Document, element_stack: &mut [Element], node: Node) {
     if let Some(current) = element_stack.last_mut() {
         current.children.push(node);
@@ -151,6 +464,105 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_error_reports_position() {
+        let input = "<svg>\n  <rect width=10/>\n</svg>";
+        let Err(Error::Parse {
+            line,
+            column,
+            offset,
+            ..
+        }) = parse(input)
+        else {
+            panic!("expected a parse error");
+        };
+
+        assert_eq!((line, column), (2, 8));
+        assert_eq!(&input[offset..offset + 6], " width");
+    }
+
+    #[test]
+    fn test_parse_error_on_mismatched_and_unclosed_tags() {
+        let err = parse("<svg><g></svg>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error at 1:11: mismatched closing tag </svg>, expected </g>"
+        );
+
+        let err = parse("<svg>\n<g/>").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "parse error at 2:5: unclosed element <svg>"
+        );
+    }
+
+    #[test]
+    fn test_parse_decodes_references_and_entities() {
+        let input = "<!DOCTYPE svg [<!ENTITY ns_svg \"http://www.w3.org/2000/svg\"><!ENTITY nested \"&ns_svg;#x\">]><svg xmlns=\"&ns_svg;\" data-a=\"&nested;\"><text>a &amp; b &lt; c &#169;&#x41;</text></svg>";
+        let doc = parse(input).unwrap();
+        let Some(Node::Element(svg)) = doc.root.get(1) else {
+            panic!("expected root <svg>");
+        };
+
+        assert_eq!(svg.attributes["xmlns"], "http://www.w3.org/2000/svg");
+        assert_eq!(svg.attributes["data-a"], "http://www.w3.org/2000/svg#x");
+        let Node::Element(text) = &svg.children[0] else {
+            panic!("expected <text>");
+        };
+        assert_eq!(
+            text.children,
+            vec![Node::Text("a & b < c \u{a9}A".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_keeps_undeclared_entities() {
+        let inputs = [
+            "<svg title=\"a&nbsp;b\">&copy; &amp;</svg>",
+            // Escaped text that looks like a reference stays escaped.
+            "<svg><a href=\"?a=1&amp;b;\"/><text>Fish &amp;chips; &amp;copy;</text></svg>",
+            "<svg>&#xFDD0;x\u{FDD1}</svg>",
+        ];
+        for input in inputs {
+            let doc = parse(input).unwrap();
+            assert_eq!(printer::print(&doc), input);
+        }
+        // The noncharacters that mark kept references survive as text.
+        let doc = parse("<svg>\u{FDD0}x\u{FDD1}</svg>").unwrap();
+        assert_eq!(printer::print(&doc), "<svg>&#xFDD0;x\u{FDD1}</svg>");
+    }
+
+    #[test]
+    fn test_parse_rejects_recursive_and_exponential_entities() {
+        let err = parse("<!DOCTYPE svg [<!ENTITY a \"&a;\">]><svg a=\"&a;\"/>").unwrap_err();
+        assert!(err.to_string().contains("nested too deeply"), "{err}");
+
+        // Six levels of thirty references each would expand to ~729 million copies.
+        let mut dtd = String::from("<!ENTITY e0 \"lol\">");
+        for level in 1..=6 {
+            let refs = format!("&e{};", level - 1).repeat(30);
+            dtd.push_str(&format!("<!ENTITY e{level} \"{refs}\">"));
+        }
+        let input = format!("<!DOCTYPE svg [{dtd}]><svg>&e6;</svg>");
+        let err = parse(&input).unwrap_err();
+        assert!(
+            err.to_string().contains("entity expansion exceeds"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_non_xml_character_references() {
+        for reference in ["&#0;", "&#x1B;", "&#xFFFE;"] {
+            let err = parse(&format!("<svg>{reference}</svg>")).unwrap_err();
+            assert!(
+                err.to_string().contains("invalid character reference"),
+                "{err}"
+            );
+        }
+        assert!(parse("<svg>&#9;&#x1F600;</svg>").is_ok());
+    }
+
     #[test]
     fn test_parse_preserves_doctype() {
         let input =
@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 use crate::plugins::{
     CleanupAttrs, CleanupEnableBackground, CleanupIds, CleanupListOfValues, CleanupNumericValues,
     CollapseGroups, ConvertColors, ConvertEllipseToCircle, ConvertOneStopGradients,
@@ -9,196 +10,428 @@ use crate::plugins::{
     RemoveTitle, RemoveUnknownsAndDefaults, RemoveUnusedNS, RemoveUselessDefs,
     RemoveUselessStrokeAndFill, RemoveXMLProcInst, SortAttrs, SortDefsChildren,
 };
+use crate::printer;
+use crate::registry::{
+    find_registered_plugin, registered_plugins, PluginPosition, RegisteredPlugin,
+};
 use crate::tree::Document;
-use std::collections::HashSet;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct OptimizeOptions {
     pub precision: usize,
     pub enable: HashSet<String>,
     pub disable: HashSet<String>,
+    /// Per-plugin parameters keyed by plugin name.
+    pub params: HashMap<String, PluginParams>,
+    /// Explicit plugin order. When set, exactly these plugins run in this
+    /// order (a name may repeat) instead of the default enabled set; `disable`
+    /// still applies.
+    pub order: Option<Vec<String>>,
+    /// Re-run the pipeline until the printed output stops shrinking.
+    pub multipass: bool,
+    /// Upper bound on pipeline runs when `multipass` is set.
+    pub max_passes: usize,
 }
 
+pub const DEFAULT_MAX_PASSES: usize = 10;
+
+/// Highest accepted precision. Rounding scales by `10^precision` in `u32`,
+/// which overflows past 9; more than 8 digits buys nothing in SVG anyway.
+pub const MAX_PRECISION: usize = 8;
+
 impl Default for OptimizeOptions {
     fn default() -> Self {
         Self {
             precision: 3,
             enable: HashSet::new(),
             disable: HashSet::new(),
+            params: HashMap::new(),
+            order: None,
+            multipass: false,
+            max_passes: DEFAULT_MAX_PASSES,
+        }
+    }
+}
+
+/// A single plugin parameter value as written in a config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum ParamValue {
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+/// Parameters for one plugin, e.g. `{ floatPrecision = 2, leadingZero = false }`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct PluginParams(BTreeMap<String, ParamValue>);
+
+impl PluginParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: ParamValue) {
+        self.0.insert(name.into(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<ParamValue> {
+        self.0.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ParamValue> {
+        self.0.get(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ParamValue)> {
+        self.0.iter()
+    }
+
+    pub fn get_bool(&self, name: &str) -> Option<bool> {
+        match self.get(name) {
+            Some(ParamValue::Bool(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn get_usize(&self, name: &str) -> Option<usize> {
+        match self.get(name) {
+            Some(ParamValue::Number(value)) if *value >= 0.0 && value.fract() == 0.0 => {
+                Some(*value as usize)
+            }
+            _ => None,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamKind {
+    Bool,
+    Integer,
+}
+
+/// The value a plugin uses when a parameter is not set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamDefault {
+    Bool(bool),
+    /// Follows [`OptimizeOptions::precision`].
+    Precision,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParamDescriptor {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub description: &'static str,
+    pub default: ParamDefault,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct PluginDescriptor {
     pub name: &'static str,
+    pub description: &'static str,
     pub enabled_by_default: bool,
+    pub params: &'static [ParamDescriptor],
 }
 
+const FLOAT_PRECISION: ParamDescriptor = ParamDescriptor {
+    name: "floatPrecision",
+    kind: ParamKind::Integer,
+    description: "Number of decimal places to keep.",
+    default: ParamDefault::Precision,
+};
+
+const LEADING_ZERO: ParamDescriptor = ParamDescriptor {
+    name: "leadingZero",
+    kind: ParamKind::Bool,
+    description: "Drop the leading zero of fractions (`0.5` becomes `.5`).",
+    default: ParamDefault::Bool(true),
+};
+
+const PRECISION_PARAMS: &[ParamDescriptor] = &[FLOAT_PRECISION];
+
+const CLEANUP_NUMERIC_VALUES_PARAMS: &[ParamDescriptor] = &[
+    FLOAT_PRECISION,
+    ParamDescriptor {
+        name: "removePx",
+        kind: ParamKind::Bool,
+        description: "Drop `px` units, which are the default.",
+        default: ParamDefault::Bool(true),
+    },
+    LEADING_ZERO,
+];
+
+const CONVERT_PATH_DATA_PARAMS: &[ParamDescriptor] = &[
+    FLOAT_PRECISION,
+    ParamDescriptor {
+        name: "makeArcs",
+        kind: ParamKind::Bool,
+        description: "Replace cubic curves that trace a circular arc with `A` commands.",
+        default: ParamDefault::Bool(true),
+    },
+    ParamDescriptor {
+        name: "noSpaceAfterFlags",
+        kind: ParamKind::Bool,
+        description: "Write arc flags without a following space (`a1 1 0 011 1`); not every parser accepts it.",
+        default: ParamDefault::Bool(false),
+    },
+];
+
+const CLEANUP_LIST_OF_VALUES_PARAMS: &[ParamDescriptor] = &[FLOAT_PRECISION, LEADING_ZERO];
+
 const PLUGIN_DESCRIPTORS: &[PluginDescriptor] = &[
     PluginDescriptor {
         name: "removeDoctype",
+        description: "Removes the `<!DOCTYPE>` declaration.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeXMLProcInst",
+        description: "Removes `<?xml ... ?>` processing instructions.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeComments",
+        description: "Removes comments.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeMetadata",
+        description: "Removes `<metadata>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeTitle",
+        description: "Removes `<title>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeDesc",
+        description: "Removes `<desc>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeEditorsNSData",
+        description: "Removes editor namespaces, elements and attributes (Inkscape, Sketch, etc.).",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeScriptElement",
+        description: "Removes `<script>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeRasterImages",
+        description: "Removes `<image>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeStyleElement",
+        description: "Removes `<style>` elements.",
         enabled_by_default: false,
+        params: &[],
     },
     PluginDescriptor {
         name: "mergeStyles",
+        description: "Merges adjacent compatible `<style>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "minifyStyles",
+        description: "Minifies safely parseable CSS in `<style>` and `style` attributes.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "inlineStyles",
+        description: "Inlines simple unique stylesheet rules into `style` attributes.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertStyleToAttrs",
+        description: "Converts `style` declarations to presentation attributes.",
         enabled_by_default: false,
+        params: &[],
     },
     PluginDescriptor {
         name: "cleanupAttrs",
+        description: "Trims and collapses whitespace in attribute values.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeUselessStrokeAndFill",
+        description: "Removes stroke and fill attributes that have no effect.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "cleanupEnableBackground",
+        description: "Removes or simplifies deprecated `enable-background` when safe.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeDimensions",
+        description: "Removes `width`/`height` from `<svg>` elements that have a `viewBox`.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "moveGroupAttrsToElems",
+        description: "Moves attributes from groups to their elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "moveElemsAttrsToGroup",
+        description: "Moves attributes shared by all elements of a group to the group.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertOneStopGradients",
+        description: "Replaces gradients with a single stop by a plain color.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "cleanupIds",
+        description: "Removes unused ids and shortens the ones that are referenced.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeUselessDefs",
+        description: "Removes `<defs>` content that is never referenced.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeEmptyContainers",
+        description: "Removes container elements without children.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeHiddenElems",
+        description: "Removes hidden elements (`display=\"none\"`, zero size, etc.).",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeEmptyText",
+        description: "Removes whitespace-only text nodes.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "collapseGroups",
+        description: "Removes redundant `<g>` elements.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertEllipseToCircle",
+        description: "Converts ellipses with equal radii to circles.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertShapeToPath",
+        description: "Converts selected basic shapes (rect, line, poly*) to paths.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertPathData",
+        description: "Optimizes path commands (relative coordinates, precision).",
         enabled_by_default: true,
+        params: CONVERT_PATH_DATA_PARAMS,
     },
     PluginDescriptor {
         name: "convertTransform",
+        description: "Collapses multiple transforms into one.",
         enabled_by_default: true,
+        params: PRECISION_PARAMS,
     },
     PluginDescriptor {
         name: "cleanupNumericValues",
+        description: "Rounds numeric attribute values and drops default units.",
         enabled_by_default: true,
+        params: CLEANUP_NUMERIC_VALUES_PARAMS,
     },
     PluginDescriptor {
         name: "cleanupListOfValues",
+        description: "Rounds numbers in list attributes such as `viewBox` and `points`.",
         enabled_by_default: true,
+        params: CLEANUP_LIST_OF_VALUES_PARAMS,
     },
     PluginDescriptor {
         name: "removeUnknownsAndDefaults",
+        description: "Removes unknown attributes and attributes set to their default value.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeNonInheritableGroupAttrs",
+        description: "Removes non-inheritable presentation attributes from `<g>`.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "mergePaths",
+        description: "Merges adjacent paths with identical attributes.",
         enabled_by_default: false,
+        params: &[],
     },
     PluginDescriptor {
         name: "convertColors",
+        description: "Shortens color values (`rgb()` to hex, `#ff0000` to `#f00`, etc.).",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeEmptyAttrs",
+        description: "Removes attributes with empty values.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "removeUnusedNS",
+        description: "Removes unused namespace declarations.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "sortAttrs",
+        description: "Sorts attributes for better gzip compression.",
         enabled_by_default: true,
+        params: &[],
     },
     PluginDescriptor {
         name: "sortDefsChildren",
+        description: "Sorts `<defs>` children to improve compression.",
         enabled_by_default: true,
+        params: &[],
     },
 ];
 
@@ -206,22 +439,93 @@ pub fn plugin_descriptors() -> &'static [PluginDescriptor] {
     PLUGIN_DESCRIPTORS
 }
 
-pub fn apply_default_pipeline(doc: &mut Document, options: &OptimizeOptions) {
+/// Hooks into a pipeline run, e.g. to trace what each plugin changed.
+/// `pass` counts from 0 and only goes above 0 in multipass mode.
+pub trait PipelineObserver {
+    fn before_plugin(&mut self, _pass: usize, _name: &str, _doc: &Document) {}
+
+    fn after_plugin(&mut self, _pass: usize, _name: &str, _doc: &Document) {}
+
+    /// An enabled plugin was left out because the document has nothing for
+    /// it to do (see `should_run_plugin`).
+    fn skipped_plugin(&mut self, _pass: usize, _name: &str) {}
+
+    /// A multipass pass grew the output and its changes were undone.
+    fn reverted_pass(&mut self, _pass: usize) {}
+}
+
+impl PipelineObserver for () {}
+
+pub fn apply_default_pipeline(doc: &mut Document, options: &OptimizeOptions) -> Result<()> {
+    apply_pipeline_with_observer(doc, options, &mut ())
+}
+
+pub fn apply_pipeline_with_observer(
+    doc: &mut Document,
+    options: &OptimizeOptions,
+    observer: &mut dyn PipelineObserver,
+) -> Result<()> {
+    if !options.multipass {
+        return apply_single_pass(doc, options, 0, observer);
+    }
+
+    let mut best_len = printer::print(doc).len();
+    for pass in 0..options.max_passes.max(1) {
+        // Keep the previous result so a pass that grows the output can be undone.
+        let previous = (pass > 0).then(|| doc.clone());
+        apply_single_pass(doc, options, pass, observer)?;
+
+        let len = printer::print(doc).len();
+        if len < best_len {
+            best_len = len;
+            continue;
+        }
+        if let Some(previous) = previous.filter(|_| len > best_len) {
+            *doc = previous;
+            observer.reverted_pass(pass);
+        }
+        break;
+    }
+    Ok(())
+}
+
+fn apply_single_pass(
+    doc: &mut Document,
+    options: &OptimizeOptions,
+    pass: usize,
+    observer: &mut dyn PipelineObserver,
+) -> Result<()> {
     let features = document_features(doc);
-    for plugin in resolve_plugins(options, &features) {
-        plugin.apply(doc);
+    for (name, plugin) in resolve_plugins(options, &features)? {
+        match plugin {
+            Some(plugin) => {
+                observer.before_plugin(pass, &name, doc);
+                plugin.apply(doc);
+                observer.after_plugin(pass, &name, doc);
+            }
+            None => observer.skipped_plugin(pass, &name),
+        }
     }
+    Ok(())
 }
 
-pub fn unknown_plugin_names(options: &OptimizeOptions) -> Vec<String> {
-    let known: HashSet<&str> = plugin_descriptors()
+/// Names of all built-in and registered plugins, in default pipeline order.
+pub fn plugin_names() -> Vec<String> {
+    default_entries()
         .iter()
-        .map(|plugin| plugin.name)
-        .collect();
+        .map(|entry| entry.name().to_string())
+        .collect()
+}
+
+pub fn unknown_plugin_names(options: &OptimizeOptions) -> Vec<String> {
+    let names = plugin_names();
+    let known: HashSet<&str> = names.iter().map(String::as_str).collect();
     let mut unknown: Vec<String> = options
         .enable
         .iter()
         .chain(options.disable.iter())
+        .chain(options.params.keys())
+        .chain(options.order.iter().flatten())
         .filter(|name| !known.contains(name.as_str()))
         .cloned()
         .collect();
@@ -230,12 +534,164 @@ pub fn unknown_plugin_names(options: &OptimizeOptions) -> Vec<String> {
     unknown
 }
 
-fn resolve_plugins(options: &OptimizeOptions, features: &DocumentFeatures) -> Vec<Box<dyn Plugin>> {
+/// Checks that every plugin referenced by `options` exists and that its
+/// parameters are valid.
+pub fn validate_options(options: &OptimizeOptions) -> Result<()> {
+    let unknown = unknown_plugin_names(options);
+    if !unknown.is_empty() {
+        return Err(Error::UnknownPlugin(unknown));
+    }
+
+    if options.precision > MAX_PRECISION {
+        return Err(Error::invalid_config(format!(
+            "precision must be at most {MAX_PRECISION}, got {}",
+            options.precision
+        )));
+    }
+
+    let invalid = invalid_plugin_params(options);
+    if !invalid.is_empty() {
+        return Err(Error::invalid_config(invalid.join("; ")));
+    }
+
+    Ok(())
+}
+
+/// Returns one message per parameter that the target plugin does not accept or
+/// whose value has the wrong type. Parameters of unknown plugins are left to
+/// [`unknown_plugin_names`]. Parameters of registered plugins are not
+/// checked: their factories read them from `options.params` as they see fit.
+pub fn invalid_plugin_params(options: &OptimizeOptions) -> Vec<String> {
+    let mut invalid = Vec::new();
+
+    for descriptor in plugin_descriptors() {
+        let Some(params) = options.params.get(descriptor.name) else {
+            continue;
+        };
+
+        for (name, value) in params.iter() {
+            let Some(param) = descriptor.params.iter().find(|param| param.name == name) else {
+                invalid.push(format!(
+                    "unknown parameter `{name}` for plugin `{}`",
+                    descriptor.name
+                ));
+                continue;
+            };
+
+            let valid = match param.kind {
+                ParamKind::Bool => params.get_bool(name).is_some(),
+                ParamKind::Integer => params.get_usize(name).is_some(),
+            };
+            if !valid {
+                let expected = match param.kind {
+                    ParamKind::Bool => "a boolean",
+                    ParamKind::Integer => "a non-negative integer",
+                };
+                invalid.push(format!(
+                    "parameter `{name}` of plugin `{}` must be {expected}, got {value:?}",
+                    descriptor.name
+                ));
+            } else if param.default == ParamDefault::Precision
+                && params.get_usize(name) > Some(MAX_PRECISION)
+            {
+                invalid.push(format!(
+                    "parameter `{name}` of plugin `{}` must be at most {MAX_PRECISION}, got {value:?}",
+                    descriptor.name
+                ));
+            }
+        }
+    }
+
+    invalid.sort();
+    invalid
+}
+
+/// One pipeline step: a built-in plugin or one added through the registry.
+enum PipelineEntry {
+    Builtin(&'static PluginDescriptor),
+    Registered(RegisteredPlugin),
+}
+
+impl PipelineEntry {
+    fn name(&self) -> &str {
+        match self {
+            Self::Builtin(descriptor) => descriptor.name,
+            Self::Registered(plugin) => &plugin.name,
+        }
+    }
+
+    fn enabled_by_default(&self) -> bool {
+        match self {
+            Self::Builtin(descriptor) => descriptor.enabled_by_default,
+            Self::Registered(plugin) => plugin.enabled_by_default,
+        }
+    }
+
+    fn build(&self, options: &OptimizeOptions) -> Result<Box<dyn Plugin>> {
+        match self {
+            Self::Builtin(descriptor) => build_plugin(descriptor.name, options),
+            Self::Registered(plugin) => Ok((plugin.factory)(options)),
+        }
+    }
+}
+
+fn default_entries() -> Vec<PipelineEntry> {
+    let registered = registered_plugins();
+    let at = |position: &PluginPosition| {
+        registered
+            .iter()
+            .filter(|plugin| plugin.position == *position)
+            .cloned()
+            .map(PipelineEntry::Registered)
+            .collect::<Vec<_>>()
+    };
+
+    let mut entries = at(&PluginPosition::Start);
+    for descriptor in plugin_descriptors() {
+        entries.extend(at(&PluginPosition::Before(descriptor.name.to_string())));
+        entries.push(PipelineEntry::Builtin(descriptor));
+        entries.extend(at(&PluginPosition::After(descriptor.name.to_string())));
+    }
+    entries.extend(at(&PluginPosition::End));
+    entries
+}
+
+fn find_entry(name: &str) -> Option<PipelineEntry> {
     plugin_descriptors()
         .iter()
-        .filter(|descriptor| is_enabled(descriptor, options))
-        .filter(|descriptor| should_run_plugin(descriptor.name, features))
-        .map(|descriptor| build_plugin(descriptor.name, options.precision))
+        .find(|descriptor| descriptor.name == name)
+        .map(PipelineEntry::Builtin)
+        .or_else(|| find_registered_plugin(name).map(PipelineEntry::Registered))
+}
+
+type ResolvedPlugins = Vec<(String, Option<Box<dyn Plugin>>)>;
+
+/// The selected plugins in run order; `None` for plugins that are enabled
+/// but not needed for this document.
+fn resolve_plugins(
+    options: &OptimizeOptions,
+    features: &DocumentFeatures,
+) -> Result<ResolvedPlugins> {
+    let selected: Vec<PipelineEntry> = match &options.order {
+        Some(order) => order
+            .iter()
+            .filter_map(|name| find_entry(name))
+            .filter(|entry| !options.disable.contains(entry.name()))
+            .collect(),
+        None => default_entries()
+            .into_iter()
+            .filter(|entry| is_enabled(entry, options))
+            .collect(),
+    };
+
+    selected
+        .into_iter()
+        .map(|entry| {
+            let plugin = should_run_plugin(entry.name(), features)
+                .then(|| entry.build(options))
+                .transpose()?;
+            Ok((entry.name().to_string(), plugin))
+        })
         .collect()
 }
 
@@ -289,20 +745,29 @@ fn should_run_plugin(name: &str, features: &DocumentFeatures) -> bool {
     }
 }
 
-fn is_enabled(descriptor: &PluginDescriptor, options: &OptimizeOptions) -> bool {
-    if options.disable.contains(descriptor.name) {
+fn is_enabled(entry: &PipelineEntry, options: &OptimizeOptions) -> bool {
+    if options.disable.contains(entry.name()) {
         return false;
     }
 
-    if options.enable.contains(descriptor.name) {
+    if options.enable.contains(entry.name()) {
         return true;
     }
 
-    descriptor.enabled_by_default
+    entry.enabled_by_default()
 }
 
-fn build_plugin(name: &str, precision: usize) -> Box<dyn Plugin> {
-    match name {
+fn float_precision(params: &PluginParams, options: &OptimizeOptions) -> usize {
+    params
+        .get_usize("floatPrecision")
+        .unwrap_or(options.precision)
+}
+
+fn build_plugin(name: &str, options: &OptimizeOptions) -> Result<Box<dyn Plugin>> {
+    let default_params = PluginParams::new();
+    let params = options.params.get(name).unwrap_or(&default_params);
+
+    let plugin: Box<dyn Plugin> = match name {
         "removeDoctype" => Box::new(RemoveDoctype),
         "removeXMLProcInst" => Box::new(RemoveXMLProcInst),
         "removeComments" => Box::new(RemoveComments),
@@ -333,27 +798,27 @@ fn build_plugin(name: &str, precision: usize) -> Box<dyn Plugin> {
         "convertEllipseToCircle" => Box::new(ConvertEllipseToCircle),
         "convertShapeToPath" => Box::new(ConvertShapeToPath),
         "convertPathData" => Box::new(ConvertPathData {
-            float_precision: precision,
+            float_precision: float_precision(params, options),
             leading_zero: true,
-            ..Default::default()
+            make_arcs: params.get_bool("makeArcs").unwrap_or(true),
+            no_space_after_flags: params.get_bool("noSpaceAfterFlags").unwrap_or(false),
         }),
         "convertTransform" => Box::new(ConvertTransform {
-            float_precision: precision,
-            deg_precision: precision,
-            ..Default::default()
+            float_precision: float_precision(params, options),
+            deg_precision: options.precision,
         }),
         "cleanupNumericValues" => Box::new(CleanupNumericValues {
-            float_precision: precision,
-            remove_px: true,
-            leading_zero: true,
+            float_precision: float_precision(params, options),
+            remove_px: params.get_bool("removePx").unwrap_or(true),
+            leading_zero: params.get_bool("leadingZero").unwrap_or(true),
         }),
         "cleanupListOfValues" => Box::new(CleanupListOfValues {
-            float_precision: precision,
+            float_precision: float_precision(params, options),
             default_px: true,
             convert_to_px: true,
-            leading_zero: true,
+            leading_zero: params.get_bool("leadingZero").unwrap_or(true),
         }),
-        "removeUnknownsAndDefaults" => Box::new(RemoveUnknownsAndDefaults::default()),
+        "removeUnknownsAndDefaults" => Box::new(RemoveUnknownsAndDefaults),
         "removeNonInheritableGroupAttrs" => Box::new(RemoveNonInheritableGroupAttrs),
         "mergePaths" => Box::new(MergePaths),
         "convertColors" => Box::new(ConvertColors),
@@ -361,8 +826,9 @@ fn build_plugin(name: &str, precision: usize) -> Box<dyn Plugin> {
         "removeUnusedNS" => Box::new(RemoveUnusedNS),
         "sortAttrs" => Box::new(SortAttrs),
         "sortDefsChildren" => Box::new(SortDefsChildren),
-        _ => unreachable!("unknown plugin: {name}"),
-    }
+        _ => return Err(Error::UnknownPlugin(vec![name.to_string()])),
+    };
+    Ok(plugin)
 }
 
 #[cfg(test)]
@@ -404,15 +870,165 @@ mod tests {
         assert!(!descriptor.enabled_by_default);
     }
 
+    #[test]
+    fn test_plugin_params_override_global_precision() {
+        let mut params = PluginParams::new();
+        params.insert("floatPrecision", ParamValue::Number(1.0));
+        let mut options = OptimizeOptions::default();
+        options.params.insert("convertPathData".to_string(), params);
+
+        let mut doc =
+            parser::parse("<svg><path d=\"M0.1234 0.5678\" opacity=\"0.1234\"/></svg>").unwrap();
+        apply_default_pipeline(&mut doc, &options).unwrap();
+
+        assert_eq!(
+            printer::print(&doc),
+            "<svg><path d=\"M.1.6\" opacity=\".123\"/></svg>"
+        );
+    }
+
+    #[test]
+    fn test_build_plugin_reports_unknown_names() {
+        let result = build_plugin("noSuchPlugin", &OptimizeOptions::default());
+        assert!(matches!(result, Err(Error::UnknownPlugin(names)) if names == ["noSuchPlugin"]));
+    }
+
+    #[test]
+    fn test_invalid_plugin_params() {
+        let mut params = PluginParams::new();
+        params.insert("removePx", ParamValue::String("yes".to_string()));
+        params.insert("unknownFlag", ParamValue::Bool(true));
+        let mut options = OptimizeOptions::default();
+        options
+            .params
+            .insert("cleanupNumericValues".to_string(), params);
+
+        assert_eq!(
+            invalid_plugin_params(&options),
+            vec![
+                "parameter `removePx` of plugin `cleanupNumericValues` must be a boolean, got String(\"yes\")".to_string(),
+                "unknown parameter `unknownFlag` for plugin `cleanupNumericValues`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_options_bounds_precision() {
+        let options = OptimizeOptions {
+            precision: 10,
+            ..OptimizeOptions::default()
+        };
+        assert_eq!(
+            validate_options(&options).unwrap_err().to_string(),
+            "invalid config: precision must be at most 8, got 10"
+        );
+
+        let mut params = PluginParams::new();
+        params.insert("floatPrecision", ParamValue::Number(12.0));
+        let mut options = OptimizeOptions::default();
+        options.params.insert("convertPathData".to_string(), params);
+        assert_eq!(
+            invalid_plugin_params(&options),
+            vec!["parameter `floatPrecision` of plugin `convertPathData` must be at most 8, got Number(12.0)".to_string()]
+        );
+
+        let options = OptimizeOptions {
+            precision: MAX_PRECISION,
+            ..OptimizeOptions::default()
+        };
+        assert!(crate::optimize_with_options(
+            "<svg><path d=\"M0 0L1.123456789 1\"/></svg>",
+            &options
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn test_explicit_order_runs_only_listed_plugins() {
+        let mut options = OptimizeOptions {
+            order: Some(vec![
+                "removeComments".to_string(),
+                "mergePaths".to_string(),
+                "removeTitle".to_string(),
+            ]),
+            ..OptimizeOptions::default()
+        };
+        options.disable.insert("removeTitle".to_string());
+
+        let mut doc = parser::parse(
+            "<svg><!--c--><title>t</title><path d=\"M0 0L1 1\"/><path d=\"M2 2L3 3\"/></svg>",
+        )
+        .unwrap();
+        apply_default_pipeline(&mut doc, &options).unwrap();
+
+        assert_eq!(
+            printer::print(&doc),
+            "<svg><title>t</title><path d=\"M0 0L1 1 M2 2L3 3\"/></svg>"
+        );
+    }
+
+    #[test]
+    fn test_unknown_plugin_names_checks_order() {
+        let options = OptimizeOptions {
+            order: Some(vec!["collapseGroups".to_string(), "missing".to_string()]),
+            ..OptimizeOptions::default()
+        };
+
+        assert_eq!(unknown_plugin_names(&options), vec!["missing".to_string()]);
+    }
+
+    #[test]
+    fn test_multipass_reaches_smaller_output() {
+        // The `use` is only dropped by removeHiddenElems, after cleanupIds has
+        // already kept `id="p"`; a second pass can then remove the id.
+        let input = "<svg><path id=\"p\" d=\"M0 0h1\"/><use href=\"#p\" display=\"none\"/></svg>";
+
+        let mut single = parser::parse(input).unwrap();
+        apply_default_pipeline(&mut single, &OptimizeOptions::default()).unwrap();
+
+        let mut multi = parser::parse(input).unwrap();
+        let options = OptimizeOptions {
+            multipass: true,
+            ..OptimizeOptions::default()
+        };
+        apply_default_pipeline(&mut multi, &options).unwrap();
+
+        assert_eq!(
+            printer::print(&single),
+            "<svg><path d=\"M0 0h1\" id=\"p\"/></svg>"
+        );
+        assert_eq!(printer::print(&multi), "<svg><path d=\"M0 0h1\"/></svg>");
+    }
+
+    #[test]
+    fn test_multipass_respects_max_passes() {
+        let input = "<svg><path id=\"p\" d=\"M0 0h1\"/><use href=\"#p\" display=\"none\"/></svg>";
+        let mut doc = parser::parse(input).unwrap();
+        let options = OptimizeOptions {
+            multipass: true,
+            max_passes: 1,
+            ..OptimizeOptions::default()
+        };
+        apply_default_pipeline(&mut doc, &options).unwrap();
+
+        assert_eq!(
+            printer::print(&doc),
+            "<svg><path d=\"M0 0h1\" id=\"p\"/></svg>"
+        );
+    }
+
     #[test]
     fn test_style_pipeline_runs_when_style_is_present() {
         let mut doc =
             parser::parse("<svg><style>.a { fill: red; }</style><rect class=\"a\"/></svg>")
                 .unwrap();
 
-        apply_default_pipeline(&mut doc, &OptimizeOptions::default());
+        apply_default_pipeline(&mut doc, &OptimizeOptions::default()).unwrap();
 
-        assert_eq!(printer::print(&doc), "<svg><rect style=\"fill:red\"/></svg>");
+        assert_eq!(
+            printer::print(&doc),
+            "<svg><rect style=\"fill:red\"/></svg>"
+        );
     }
 
     #[test]
@@ -422,7 +1038,7 @@ mod tests {
         )
         .unwrap();
 
-        apply_default_pipeline(&mut doc, &OptimizeOptions::default());
+        apply_default_pipeline(&mut doc, &OptimizeOptions::default()).unwrap();
 
         assert_eq!(
             printer::print(&doc),
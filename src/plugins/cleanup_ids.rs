@@ -115,7 +115,12 @@ fn rewrite_attr_value(key: &str, value: &str, rename_map: &HashMap<String, Strin
     ) {
         return value
             .split_whitespace()
-            .map(|token| rename_map.get(token).cloned().unwrap_or_else(|| token.to_string()))
+            .map(|token| {
+                rename_map
+                    .get(token)
+                    .cloned()
+                    .unwrap_or_else(|| token.to_string())
+            })
             .collect::<Vec<_>>()
             .join(" ");
     }
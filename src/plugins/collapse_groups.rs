@@ -19,10 +19,7 @@ fn collapse_groups_in_nodes(nodes: &mut Vec<Node>, parent_name: &str) {
     }
 
     // 2. Collapse
-    let parent_prevents_unwrap = match parent_name {
-        "switch" | "foreignObject" => true,
-        _ => false,
-    };
+    let parent_prevents_unwrap = matches!(parent_name, "switch" | "foreignObject");
 
     if parent_prevents_unwrap {
         return;
@@ -4,19 +4,16 @@ use std::collections::HashSet;
 use std::sync::OnceLock;
 
 pub fn find_used_ids(node: &Node, used_ids: &mut HashSet<String>) {
-    match node {
-        Node::Element(elem) => {
-            // Check all attributes for references
-            for (key, value) in &elem.attributes {
-                extract_ids_from_attr(key, value, used_ids);
-            }
+    if let Node::Element(elem) = node {
+        // Check all attributes for references
+        for (key, value) in &elem.attributes {
+            extract_ids_from_attr(key, value, used_ids);
+        }
 
-            // Recurse
-            for child in &elem.children {
-                find_used_ids(child, used_ids);
-            }
+        // Recurse
+        for child in &elem.children {
+            find_used_ids(child, used_ids);
         }
-        _ => {}
     }
 }
 
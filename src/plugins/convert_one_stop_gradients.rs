@@ -179,8 +179,7 @@ mod tests {
 
     #[test]
     fn test_keep_zero_stop_gradient() {
-        let input =
-            r#"<svg><defs><linearGradient id="g1"/></defs><rect fill="url(#g1)"/></svg>"#;
+        let input = r#"<svg><defs><linearGradient id="g1"/></defs><rect fill="url(#g1)"/></svg>"#;
 
         let mut doc = parser::parse(input).unwrap();
         ConvertOneStopGradients.apply(&mut doc);
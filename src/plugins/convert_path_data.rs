@@ -1,9 +1,14 @@
 use crate::plugins::Plugin;
-use crate::tree::{Document, Node};
+use crate::tree::{Document, Element, Node};
 
 pub struct ConvertPathData {
     pub float_precision: usize,
     pub leading_zero: bool,
+    /// Replace cubic curves that follow a circular arc with `A` commands.
+    pub make_arcs: bool,
+    /// Write arc flags without a space after them (`a1 1 0 011 1`). Shorter,
+    /// but some renderers and editors fail to parse it.
+    pub no_space_after_flags: bool,
     // Add more opts as needed
 }
 
@@ -12,32 +17,68 @@ impl Default for ConvertPathData {
         Self {
             float_precision: 3,
             leading_zero: true,
+            make_arcs: true,
+            no_space_after_flags: false,
         }
     }
 }
 
 impl Plugin for ConvertPathData {
     fn apply(&self, doc: &mut Document) {
-        process_paths(&mut doc.root, self);
+        // Without a CSS engine, any stylesheet mentioning markers might put
+        // them on any path.
+        let stylesheet_markers = stylesheet_mentions_markers(&doc.root);
+        process_paths(&mut doc.root, self, stylesheet_markers);
     }
 }
 
-fn process_paths(nodes: &mut Vec<Node>, opts: &ConvertPathData) {
+fn process_paths(nodes: &mut Vec<Node>, opts: &ConvertPathData, inherited_markers: bool) {
     for node in nodes {
         if let Node::Element(elem) = node {
+            // Marker properties inherit, so a marked group marks its paths.
+            let has_markers = inherited_markers || sets_markers(elem);
             if elem.name == "path" {
                 if let Some(d) = elem.attributes.get_mut("d") {
-                    let new_d = optimize_path_data(d, opts);
+                    let new_d = optimize_path_data(d, opts, has_markers);
                     *d = new_d;
                 }
             }
-            process_paths(&mut elem.children, opts);
+            process_paths(&mut elem.children, opts, has_markers);
         }
     }
 }
 
-fn optimize_path_data(d: &str, opts: &ConvertPathData) -> String {
-    let commands = parse_path_data(d);
+fn sets_markers(elem: &Element) -> bool {
+    ["marker", "marker-start", "marker-mid", "marker-end"]
+        .iter()
+        .any(|name| elem.attributes.contains_key(*name))
+        || elem
+            .attributes
+            .get("style")
+            .is_some_and(|style| style.contains("marker"))
+}
+
+fn stylesheet_mentions_markers(nodes: &[Node]) -> bool {
+    nodes.iter().any(|node| match node {
+        Node::Element(elem) if elem.name == "style" => elem.children.iter().any(
+            |child| matches!(child, Node::Text(css) | Node::Cdata(css) if css.contains("marker")),
+        ),
+        Node::Element(elem) => stylesheet_mentions_markers(&elem.children),
+        _ => false,
+    })
+}
+
+fn optimize_path_data(d: &str, opts: &ConvertPathData, has_markers: bool) -> String {
+    let commands = expand_shorthands(parse_path_data(d));
+    let mut commands = straighten_curves(commands, opts.float_precision);
+    // Markers sit on every vertex, so none may be dropped or merged away.
+    if !has_markers {
+        commands = remove_useless_segments(commands, opts.float_precision);
+        if opts.make_arcs {
+            commands = convert_curves_to_arcs(commands, opts.float_precision);
+        }
+    }
+    let commands = use_shorthands(commands, opts.float_precision);
     stringify_optimized(&commands, opts)
 }
 
@@ -68,6 +109,9 @@ fn parse_path_data(d: &str) -> Vec<Command> {
     // Current pen position
     let mut cur_x = 0.0;
     let mut cur_y = 0.0;
+    // Start of the current subpath, where `Z` returns the pen to.
+    let mut start_x = 0.0;
+    let mut start_y = 0.0;
 
     // Previous control point for S/T (absolute)
     // If previous was C/S, ctrl point is second control point. Else current point (reflection).
@@ -82,6 +126,7 @@ fn parse_path_data(d: &str) -> Vec<Command> {
 
     loop {
         lexer.skip_ws_comma();
+        let pos = lexer.pos;
         let c_opt = lexer.peek_char();
         if c_opt.is_none() {
             break;
@@ -107,23 +152,28 @@ fn parse_path_data(d: &str) -> Vec<Command> {
         current_cmd_char = Some(cmd_char);
 
         match cmd_char {
+            // Further coordinate pairs are implicit line-tos, read as `L`/`l`.
             'M' => {
-                while let (Some(x), Some(y)) = (lexer.read_number(), lexer.read_number()) {
+                if let (Some(x), Some(y)) = (lexer.read_number(), lexer.read_number()) {
                     commands.push(Command::Move(x, y));
                     cur_x = x;
                     cur_y = y;
-                    current_cmd_char = Some('L'); // Subsequent are Line
+                    start_x = x;
+                    start_y = y;
                 }
+                current_cmd_char = Some('L');
             }
             'm' => {
-                while let (Some(dx), Some(dy)) = (lexer.read_number(), lexer.read_number()) {
+                if let (Some(dx), Some(dy)) = (lexer.read_number(), lexer.read_number()) {
                     let nx = cur_x + dx;
                     let ny = cur_y + dy;
                     commands.push(Command::Move(nx, ny));
                     cur_x = nx;
                     cur_y = ny;
-                    current_cmd_char = Some('l');
+                    start_x = nx;
+                    start_y = ny;
                 }
+                current_cmd_char = Some('l');
             }
             'L' => {
                 while let (Some(x), Some(y)) = (lexer.read_number(), lexer.read_number()) {
@@ -169,11 +219,8 @@ fn parse_path_data(d: &str) -> Vec<Command> {
             }
             'Z' | 'z' => {
                 commands.push(Command::Close);
-                // No args.
-                // Need start point of subpath? For Z calculation?
-                // Z closes to most recent Move.
-                // But for next command, pos is subpath start.
-                // Tracking subpath start is needed for correct pen pos after Z.
+                cur_x = start_x;
+                cur_y = start_y;
             }
             'C' => {
                 while let (Some(x1), Some(y1), Some(x2), Some(y2), Some(x), Some(y)) = (
@@ -370,19 +417,24 @@ fn parse_path_data(d: &str) -> Vec<Command> {
                 break;
             }
         }
-    }
 
-    // Fix Close Z/z position tracking
-    // Parsing Z should implicitly move pen to start of subpath.
-    // For exact tracking we need to store start_x/y of last Move.
-    // Simplifying for now - Z does not produce coords.
+        // Stop at garbage that no command can consume.
+        if lexer.pos == pos {
+            break;
+        }
+    }
 
     commands
 }
 
-fn format_num(n: f64, p: usize) -> String {
+fn round_to(n: f64, p: usize) -> f64 {
     let factor = 10u32.pow(p as u32) as f64;
-    let rounded = (n * factor).round() / factor;
+    // Adding zero turns a rounded `-0` into `0`.
+    (n * factor).round() / factor + 0.0
+}
+
+fn format_num(n: f64, p: usize) -> String {
+    let rounded = round_to(n, p);
     // Remove leading zeros etc.
     let s = rounded.to_string();
     if s.starts_with("0.") {
@@ -394,357 +446,725 @@ fn format_num(n: f64, p: usize) -> String {
     }
 }
 
-fn needs_separator(prev: char, next: char) -> bool {
-    matches!(prev, '0'..='9' | '.')
-        && matches!(next, '0'..='9' | '.' | '+')
+/// One argument of a command as written.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(String),
+    /// An arc flag. Flags are a single character, so whatever follows one
+    /// needs no separator.
+    Flag(bool),
 }
 
-fn append_fragment(out: &mut String, fragment: &str) {
-    if fragment.is_empty() {
-        return;
-    }
+/// A command as written: its letter and arguments.
+#[derive(Debug, Clone, PartialEq)]
+struct Encoded {
+    letter: char,
+    args: Vec<Token>,
+}
 
-    if let (Some(prev), Some(next)) = (out.chars().last(), fragment.chars().next()) {
-        if needs_separator(prev, next) {
-            out.push(' ');
+impl Encoded {
+    fn new(letter: char, numbers: &[f64], p: usize) -> Self {
+        Self {
+            letter,
+            args: numbers
+                .iter()
+                .map(|&n| Token::Number(format_num(n, p)))
+                .collect(),
         }
     }
+}
 
-    out.push_str(fragment);
+/// What the path written so far ends with.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+enum Last {
+    #[default]
+    Letter,
+    Number {
+        has_dot: bool,
+    },
+    Flag,
 }
 
-fn format_pair(a: f64, b: f64, p: usize) -> String {
-    let mut out = format_num(a, p);
-    let second = format_num(b, p);
-    append_fragment(&mut out, &second);
-    out
+/// Writes commands in their shortest legal form: a letter is left out when
+/// the previous command implies it, and arguments are only separated where
+/// they would otherwise run together (`1-2` and `.5.5` need no spaces). Arc
+/// flags are followed by a space unless `glue_flags` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct PathWriter {
+    /// The letter a bare argument list repeats: the previous letter, or
+    /// `L`/`l` after `M`/`m`.
+    implicit: Option<char>,
+    last: Last,
+    glue_flags: bool,
 }
 
-fn best_move_fragment(cur_x: f64, cur_y: f64, x: f64, y: f64, p: usize) -> String {
-    let abs_str = format!("M{}", format_pair(x, y, p));
-    let rel_str = format!("m{}", format_pair(x - cur_x, y - cur_y, p));
+impl PathWriter {
+    fn new(glue_flags: bool) -> Self {
+        Self {
+            glue_flags,
+            ..Self::default()
+        }
+    }
 
-    if rel_str.len() < abs_str.len() {
-        rel_str
-    } else {
-        abs_str
+    fn needs_separator(&self, token: &Token) -> bool {
+        match (self.last, token) {
+            (Last::Letter, _) => false,
+            (Last::Flag, Token::Number(n)) if n.starts_with('-') => false,
+            (Last::Flag, _) => !self.glue_flags,
+            (Last::Number { .. }, Token::Flag(_)) => true,
+            (Last::Number { has_dot }, Token::Number(n)) => match n.as_bytes()[0] {
+                b'-' => false,
+                b'.' => !has_dot,
+                _ => true,
+            },
+        }
+    }
+
+    fn write(&mut self, out: &mut String, cmd: &Encoded) {
+        if self.implicit != Some(cmd.letter) || cmd.args.is_empty() {
+            out.push(cmd.letter);
+            self.last = Last::Letter;
+        }
+        for token in &cmd.args {
+            if self.needs_separator(token) {
+                out.push(' ');
+            }
+            match token {
+                Token::Number(n) => {
+                    out.push_str(n);
+                    self.last = Last::Number {
+                        has_dot: n.contains('.'),
+                    };
+                }
+                Token::Flag(flag) => {
+                    out.push(if *flag { '1' } else { '0' });
+                    self.last = Last::Flag;
+                }
+            }
+        }
+        self.implicit = match cmd.letter {
+            'M' => Some('L'),
+            'm' => Some('l'),
+            'Z' | 'z' => None,
+            letter => Some(letter),
+        };
+    }
+
+    /// How many characters writing `cmd` next would add.
+    fn cost(self, cmd: &Encoded) -> usize {
+        let mut writer = self;
+        let mut out = String::new();
+        writer.write(&mut out, cmd);
+        out.len()
     }
 }
 
-fn best_line_fragment(cur_x: f64, cur_y: f64, x: f64, y: f64, p: usize) -> String {
-    let abs_x_s = format_num(x, p);
-    let abs_y_s = format_num(y, p);
-    let rel_x_s = format_num(x - cur_x, p);
-    let rel_y_s = format_num(y - cur_y, p);
+/// Where the pen ends up after `cmd`, given the current point and the start
+/// of the current subpath.
+fn end_point(cmd: &Command, cur: (f64, f64), subpath_start: (f64, f64)) -> (f64, f64) {
+    match *cmd {
+        Command::Move(x, y)
+        | Command::Line(x, y)
+        | Command::Curve(_, _, _, _, x, y)
+        | Command::SmoothCurve(_, _, x, y)
+        | Command::Quad(_, _, x, y)
+        | Command::SmoothQuad(x, y)
+        | Command::Arc(_, _, _, _, _, x, y) => (x, y),
+        Command::Horiz(x) => (x, cur.1),
+        Command::Vert(y) => (cur.0, y),
+        Command::Close => subpath_start,
+    }
+}
 
-    let rel_l = format!("l{}", format_pair(x - cur_x, y - cur_y, p));
-    let abs_l = format!("L{}", format_pair(x, y, p));
-    let mut best_str = if rel_l.len() <= abs_l.len() {
-        rel_l
-    } else {
-        abs_l
+/// The ways to write `cmd` from `cur`. Candidates come in order of preference
+/// for ties: relative before absolute (except for `M`), and `H`/`V` before
+/// `L` for axis-aligned lines.
+fn encodings(cmd: &Command, cur: (f64, f64), p: usize) -> Vec<Encoded> {
+    // Every number written lies on the 10^-p grid, so a renderer puts the pen
+    // at the rounded position whichever encodings came before. Measuring from
+    // there instead of the exact position keeps rounding errors from adding
+    // up along the path.
+    let (cx, cy) = (round_to(cur.0, p), round_to(cur.1, p));
+    let delta = |n: f64, c: f64| round_to(n, p) - c;
+    let both = |abs: char, abs_args: &[f64], rel: char, rel_args: &[f64]| {
+        vec![
+            Encoded::new(rel, rel_args, p),
+            Encoded::new(abs, abs_args, p),
+        ]
     };
+    match *cmd {
+        Command::Move(x, y) => vec![
+            Encoded::new('M', &[x, y], p),
+            Encoded::new('m', &[delta(x, cx), delta(y, cy)], p),
+        ],
+        Command::Line(x, y) => {
+            let mut candidates = Vec::new();
+            if delta(x, cx) == 0.0 {
+                candidates.extend(both('V', &[y], 'v', &[delta(y, cy)]));
+            }
+            if delta(y, cy) == 0.0 {
+                candidates.extend(both('H', &[x], 'h', &[delta(x, cx)]));
+            }
+            candidates.extend(both('L', &[x, y], 'l', &[delta(x, cx), delta(y, cy)]));
+            candidates
+        }
+        Command::Horiz(x) => both('H', &[x], 'h', &[delta(x, cx)]),
+        Command::Vert(y) => both('V', &[y], 'v', &[delta(y, cy)]),
+        Command::Curve(x1, y1, x2, y2, x, y) => both(
+            'C',
+            &[x1, y1, x2, y2, x, y],
+            'c',
+            &[
+                delta(x1, cx),
+                delta(y1, cy),
+                delta(x2, cx),
+                delta(y2, cy),
+                delta(x, cx),
+                delta(y, cy),
+            ],
+        ),
+        Command::SmoothCurve(x2, y2, x, y) => both(
+            'S',
+            &[x2, y2, x, y],
+            's',
+            &[delta(x2, cx), delta(y2, cy), delta(x, cx), delta(y, cy)],
+        ),
+        Command::Quad(x1, y1, x, y) => both(
+            'Q',
+            &[x1, y1, x, y],
+            'q',
+            &[delta(x1, cx), delta(y1, cy), delta(x, cx), delta(y, cy)],
+        ),
+        Command::SmoothQuad(x, y) => both('T', &[x, y], 't', &[delta(x, cx), delta(y, cy)]),
+        Command::Arc(rx, ry, rot, large_arc, sweep, x, y) => {
+            let arc = |letter: char, x: f64, y: f64| {
+                let mut enc = Encoded::new(letter, &[rx, ry, rot], p);
+                enc.args
+                    .extend([Token::Flag(large_arc), Token::Flag(sweep)]);
+                enc.args.extend(Encoded::new(letter, &[x, y], p).args);
+                enc
+            };
+            vec![arc('a', delta(x, cx), delta(y, cy)), arc('A', x, y)]
+        }
+        Command::Close => vec![Encoded {
+            letter: 'z',
+            args: Vec::new(),
+        }],
+    }
+}
 
-    if (y - cur_y).abs() < f64::EPSILON {
-        let abs_h = format!("H{}", abs_x_s);
-        let rel_h = format!("h{}", rel_x_s);
-        let best_h = if rel_h.len() <= abs_h.len() {
-            rel_h
-        } else {
-            abs_h
+/// The length of the shortest encoding of `cmd` written on its own.
+fn command_len(cmd: &Command, cur: (f64, f64), p: usize) -> usize {
+    encodings(cmd, cur, p)
+        .iter()
+        .map(|enc| PathWriter::default().cost(enc))
+        .min()
+        .unwrap_or(0)
+}
+
+/// One way to write the path up to some command: the writer state it ends
+/// in, its length, and the step and encoding it came from.
+#[derive(Debug, Clone, Copy)]
+struct Step {
+    writer: PathWriter,
+    len: usize,
+    from: usize,
+    choice: usize,
+}
+
+/// Picks an encoding for every command so the whole path comes out as short
+/// as possible. A choice only affects later ones through the writer state
+/// (which letter may be left out, whether a separator is needed), so it is
+/// enough to keep the shortest way to reach each state after every command.
+fn stringify_optimized(commands: &[Command], opts: &ConvertPathData) -> String {
+    let p = opts.float_precision;
+    let mut candidates = Vec::with_capacity(commands.len());
+    let mut layers = vec![vec![Step {
+        writer: PathWriter::new(opts.no_space_after_flags),
+        len: 0,
+        from: 0,
+        choice: 0,
+    }]];
+
+    let mut cur = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+    let mut scratch = String::new();
+    for cmd in commands {
+        let encoded = encodings(cmd, cur, p);
+        let mut next: Vec<Step> = Vec::new();
+        for (from, step) in layers[layers.len() - 1].iter().enumerate() {
+            for (choice, enc) in encoded.iter().enumerate() {
+                let mut writer = step.writer;
+                scratch.clear();
+                writer.write(&mut scratch, enc);
+                let candidate = Step {
+                    writer,
+                    len: step.len + scratch.len(),
+                    from,
+                    choice,
+                };
+                // Ties keep the earlier, preferred encoding.
+                match next.iter_mut().find(|other| other.writer == writer) {
+                    Some(other) if candidate.len < other.len => *other = candidate,
+                    Some(_) => {}
+                    None => next.push(candidate),
+                }
+            }
+        }
+        layers.push(next);
+        candidates.push(encoded);
+
+        if let Command::Move(x, y) = *cmd {
+            subpath_start = (x, y);
+        }
+        cur = end_point(cmd, cur, subpath_start);
+    }
+
+    let last = &layers[layers.len() - 1];
+    let mut index = (0..last.len())
+        .min_by_key(|&index| last[index].len)
+        .unwrap_or(0);
+    let mut choices = vec![0; commands.len()];
+    for (i, layer) in layers.iter().enumerate().skip(1).rev() {
+        choices[i - 1] = layer[index].choice;
+        index = layer[index].from;
+    }
+
+    let mut s = String::new();
+    let mut writer = PathWriter::new(opts.no_space_after_flags);
+    for (encoded, choice) in candidates.iter().zip(choices) {
+        writer.write(&mut s, &encoded[choice]);
+    }
+    s
+}
+
+/// `point` mirrored through `center`.
+fn reflect(point: (f64, f64), center: (f64, f64)) -> (f64, f64) {
+    (2.0 * center.0 - point.0, 2.0 * center.1 - point.1)
+}
+
+/// Rewrites `S` and `T` as full `C` and `Q` commands, so the other passes can
+/// change or drop a curve without moving the implicit control point of the
+/// one after it.
+fn expand_shorthands(commands: Vec<Command>) -> Vec<Command> {
+    let mut out = Vec::with_capacity(commands.len());
+    let mut cur = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+    // The last control point of the previous command, if it was a cubic
+    // or a quadratic curve respectively.
+    let mut cubic_control = None;
+    let mut quad_control = None;
+
+    for cmd in commands {
+        let expanded = match cmd {
+            Command::SmoothCurve(x2, y2, x, y) => {
+                let (x1, y1) = cubic_control.map_or(cur, |control| reflect(control, cur));
+                Command::Curve(x1, y1, x2, y2, x, y)
+            }
+            Command::SmoothQuad(x, y) => {
+                let (x1, y1) = quad_control.map_or(cur, |control| reflect(control, cur));
+                Command::Quad(x1, y1, x, y)
+            }
+            cmd => cmd,
         };
-        if best_h.len() <= best_str.len() {
-            best_str = best_h;
+
+        (cubic_control, quad_control) = match expanded {
+            Command::Curve(_, _, x2, y2, _, _) => (Some((x2, y2)), None),
+            Command::Quad(x1, y1, _, _) => (None, Some((x1, y1))),
+            _ => (None, None),
+        };
+        if let Command::Move(x, y) = expanded {
+            subpath_start = (x, y);
         }
+        cur = end_point(&expanded, cur, subpath_start);
+        out.push(expanded);
     }
 
-    if (x - cur_x).abs() < f64::EPSILON {
-        let abs_v = format!("V{}", abs_y_s);
-        let rel_v = format!("v{}", rel_y_s);
-        let best_v = if rel_v.len() <= abs_v.len() {
-            rel_v
-        } else {
-            abs_v
+    out
+}
+
+/// Writes curves whose first control point mirrors the previous one as `S`
+/// or `T`, within rounding distance at precision `p`.
+fn use_shorthands(commands: Vec<Command>, p: usize) -> Vec<Command> {
+    let error = 10f64.powi(-(p as i32));
+    let mut out = Vec::with_capacity(commands.len());
+    let mut cur = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+    // The control point a following `S`/`T` would reflect. For `T` this is
+    // the implied point, so rounding can't drift along a chain of them.
+    let mut cubic_control = None;
+    let mut quad_control = None;
+
+    for cmd in commands {
+        let mirrors = |control: Option<(f64, f64)>, point: (f64, f64)| {
+            let implied = control.map_or(cur, |control| reflect(control, cur));
+            distance(implied, point) < error / 2.0
         };
-        if best_v.len() <= best_str.len() {
-            best_str = best_v;
+        let (shortened, cubic, quad) = match cmd {
+            Command::Curve(x1, y1, x2, y2, x, y) if mirrors(cubic_control, (x1, y1)) => {
+                (Command::SmoothCurve(x2, y2, x, y), Some((x2, y2)), None)
+            }
+            Command::Curve(_, _, x2, y2, _, _) => (cmd, Some((x2, y2)), None),
+            Command::Quad(x1, y1, x, y) if mirrors(quad_control, (x1, y1)) => {
+                let implied = quad_control.map_or(cur, |control| reflect(control, cur));
+                (Command::SmoothQuad(x, y), None, Some(implied))
+            }
+            Command::Quad(x1, y1, _, _) => (cmd, None, Some((x1, y1))),
+            cmd => (cmd, None, None),
+        };
+
+        (cubic_control, quad_control) = (cubic, quad);
+        if let Command::Move(x, y) = shortened {
+            subpath_start = (x, y);
         }
+        cur = end_point(&shortened, cur, subpath_start);
+        out.push(shortened);
     }
 
-    best_str
+    out
 }
 
-fn serialize_move_line_run(
-    commands: &[Command],
-    cur_x: f64,
-    cur_y: f64,
-    p: usize,
-) -> (String, f64, f64) {
-    let Command::Move(move_x, move_y) = commands[0] else {
-        unreachable!("move-line run must start with Move");
-    };
+fn distance_to_segment(point: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let length_sq = (b.0 - a.0).powi(2) + (b.1 - a.1).powi(2);
+    if length_sq == 0.0 {
+        return distance(point, a);
+    }
+    let t = (((point.0 - a.0) * (b.0 - a.0) + (point.1 - a.1) * (b.1 - a.1)) / length_sq)
+        .clamp(0.0, 1.0);
+    distance(point, (a.0 + t * (b.0 - a.0), a.1 + t * (b.1 - a.1)))
+}
 
-    let mut naive = best_move_fragment(cur_x, cur_y, move_x, move_y, p);
-    let mut naive_x = move_x;
-    let mut naive_y = move_y;
-    for cmd in &commands[1..] {
-        let Command::Line(x, y) = cmd else {
-            unreachable!("move-line run must only contain Line commands after Move");
+/// Replaces curves whose control points lie within rounding distance of the
+/// chord with lines. A curve stays inside the hull of its control points, so
+/// it then never strays further than that from the line either.
+fn straighten_curves(commands: Vec<Command>, p: usize) -> Vec<Command> {
+    let error = 10f64.powi(-(p as i32));
+    let mut out = Vec::with_capacity(commands.len());
+    let mut cur = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
+
+    for cmd in &commands {
+        let flat =
+            |control: (f64, f64), end: (f64, f64)| distance_to_segment(control, cur, end) <= error;
+        let straightened = match *cmd {
+            Command::Curve(x1, y1, x2, y2, x, y)
+                if flat((x1, y1), (x, y)) && flat((x2, y2), (x, y)) =>
+            {
+                Command::Line(x, y)
+            }
+            Command::Quad(x1, y1, x, y) if flat((x1, y1), (x, y)) => Command::Line(x, y),
+            _ => cmd.clone(),
         };
-        naive.push_str(&best_line_fragment(naive_x, naive_y, *x, *y, p));
-        naive_x = *x;
-        naive_y = *y;
+
+        if let Command::Move(x, y) = straightened {
+            subpath_start = (x, y);
+        }
+        cur = end_point(&straightened, cur, subpath_start);
+        out.push(straightened);
     }
 
-    let mut implicit_abs = format!("M{}", format_pair(move_x, move_y, p));
-    for cmd in &commands[1..] {
-        let Command::Line(x, y) = cmd else {
-            unreachable!("move-line run must only contain Line commands after Move");
-        };
-        append_fragment(&mut implicit_abs, &format_pair(*x, *y, p));
+    out
+}
+
+/// Whether `cmd`, drawn from `cur`, neither moves the pen nor draws anything
+/// visible at precision `error`. Expects shorthand curves to be expanded.
+fn is_zero_length(cmd: &Command, cur: (f64, f64), error: f64) -> bool {
+    let at_cur = |point: (f64, f64)| distance(point, cur) < error / 2.0;
+    match *cmd {
+        Command::Line(x, y) | Command::Arc(_, _, _, _, _, x, y) => at_cur((x, y)),
+        Command::Horiz(x) => at_cur((x, cur.1)),
+        Command::Vert(y) => at_cur((cur.0, y)),
+        Command::Curve(x1, y1, x2, y2, x, y) => {
+            at_cur((x1, y1)) && at_cur((x2, y2)) && at_cur((x, y))
+        }
+        Command::Quad(x1, y1, x, y) => at_cur((x1, y1)) && at_cur((x, y)),
+        Command::Move(..) | Command::SmoothCurve(..) | Command::SmoothQuad(..) | Command::Close => {
+            false
+        }
     }
+}
 
-    let mut implicit_rel = format!("m{}", format_pair(move_x - cur_x, move_y - cur_y, p));
-    let mut rel_x = move_x;
-    let mut rel_y = move_y;
-    for cmd in &commands[1..] {
-        let Command::Line(x, y) = cmd else {
-            unreachable!("move-line run must only contain Line commands after Move");
+/// Drops zero-length segments and lines back to the subpath start right
+/// before a `z`, which draws that line itself. A subpath made only of
+/// zero-length segments is kept as is: with round or square caps it renders
+/// as a dot.
+fn remove_useless_segments(commands: Vec<Command>, p: usize) -> Vec<Command> {
+    let error = 10f64.powi(-(p as i32));
+    let mut out = Vec::with_capacity(commands.len());
+    let mut cur = (0.0, 0.0);
+
+    let mut index = 0;
+    while index < commands.len() {
+        let mut end = index + 1;
+        while end < commands.len() && !matches!(commands[end], Command::Move(..)) {
+            end += 1;
+        }
+        let subpath = &commands[index..end];
+        let subpath_start = match subpath[0] {
+            Command::Move(x, y) => (x, y),
+            _ => cur,
         };
-        append_fragment(&mut implicit_rel, &format_pair(*x - rel_x, *y - rel_y, p));
-        rel_x = *x;
-        rel_y = *y;
+
+        // Pair every command with its start point and whether it's useless.
+        let mut segments = Vec::with_capacity(subpath.len());
+        for cmd in subpath {
+            let zero = is_zero_length(cmd, cur, error);
+            segments.push((cmd, cur, zero));
+            cur = end_point(cmd, cur, subpath_start);
+        }
+
+        let is_dot = segments
+            .iter()
+            .all(|(cmd, _, zero)| *zero || matches!(cmd, Command::Move(..) | Command::Close));
+        if is_dot {
+            out.extend(subpath.iter().cloned());
+            index = end;
+            continue;
+        }
+
+        let start = out.len();
+        for (cmd, from, zero) in segments {
+            if zero {
+                continue;
+            }
+            let line_to_start = matches!(
+                out[start..].last(),
+                Some(Command::Line(..) | Command::Horiz(_) | Command::Vert(_))
+            ) && distance(from, subpath_start) < error / 2.0;
+            if matches!(cmd, Command::Close) && line_to_start {
+                out.pop();
+            }
+            out.push(cmd.clone());
+        }
+        index = end;
     }
 
-    let best = [naive, implicit_abs, implicit_rel]
-        .into_iter()
-        .min_by_key(|candidate| candidate.len())
-        .unwrap();
+    out
+}
 
-    (best, naive_x, naive_y)
+/// SVGO's `makeArcs` defaults: a curve is an arc if sampled points stay
+/// within `ARC_THRESHOLD` rounding units, and within `ARC_TOLERANCE` percent
+/// of the radius, of the circle.
+const ARC_THRESHOLD: f64 = 2.5;
+const ARC_TOLERANCE: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy)]
+struct Circle {
+    cx: f64,
+    cy: f64,
+    r: f64,
 }
 
-fn stringify_optimized(commands: &[Command], opts: &ConvertPathData) -> String {
-    let mut s = String::new();
-    let p = opts.float_precision;
+/// A cubic curve that follows a circle, traversing `angle` radians of it
+/// and straying at most `deviation` from it.
+#[derive(Debug, Clone, Copy)]
+struct ArcFit {
+    circle: Circle,
+    sweep: bool,
+    angle: f64,
+    deviation: f64,
+}
 
-    // State for optimization
-    let mut cur_x = 0.0;
-    let mut cur_y = 0.0;
-    let mut subpath_start_x = 0.0;
-    let mut subpath_start_y = 0.0;
+fn cubic_point(
+    p0: (f64, f64),
+    p1: (f64, f64),
+    p2: (f64, f64),
+    p3: (f64, f64),
+    t: f64,
+) -> (f64, f64) {
+    let mt = 1.0 - t;
+    let a = mt * mt * mt;
+    let b = 3.0 * mt * mt * t;
+    let c = 3.0 * mt * t * t;
+    let d = t * t * t;
+    (
+        a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+        a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+    )
+}
+
+fn cross(origin: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - origin.0) * (b.1 - origin.1) - (a.1 - origin.1) * (b.0 - origin.0)
+}
+
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+/// The circle through three points, or `None` if they are collinear.
+fn circle_through(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> Option<Circle> {
+    let d = 2.0 * cross(a, b, c);
+    if d.abs() < 1e-12 {
+        return None;
+    }
+    let a2 = a.0 * a.0 + a.1 * a.1;
+    let b2 = b.0 * b.0 + b.1 * b.1;
+    let c2 = c.0 * c.0 + c.1 * c.1;
+    let cx = (a2 * (b.1 - c.1) + b2 * (c.1 - a.1) + c2 * (a.1 - b.1)) / d;
+    let cy = (a2 * (c.0 - b.0) + b2 * (a.0 - c.0) + c2 * (b.0 - a.0)) / d;
+    let r = distance((cx, cy), a);
+    r.is_finite().then_some(Circle { cx, cy, r })
+}
+
+fn arc_tolerance(r: f64, error: f64) -> f64 {
+    (ARC_THRESHOLD * error).min(ARC_TOLERANCE * r / 100.0)
+}
+
+/// Checks whether the cubic from `start` through `curve` (`x1 y1 x2 y2 x y`)
+/// lies on a circle, within the tolerance for rounding unit `error`.
+fn fit_arc(start: (f64, f64), curve: [f64; 6], error: f64) -> Option<ArcFit> {
+    let p1 = (curve[0], curve[1]);
+    let p2 = (curve[2], curve[3]);
+    let end = (curve[4], curve[5]);
+    let mid = cubic_point(start, p1, p2, end, 0.5);
+    let circle = circle_through(start, mid, end)?;
+    let center = (circle.cx, circle.cy);
+
+    let deviation = [0.125, 0.25, 0.375, 0.625, 0.75, 0.875]
+        .iter()
+        .map(|&t| {
+            let point = cubic_point(start, p1, p2, end, t);
+            (distance(point, center) - circle.r).abs()
+        })
+        .fold(0.0, f64::max);
+    if deviation > arc_tolerance(circle.r, error) {
+        return None;
+    }
+
+    // In SVG's y-down coordinates a positive cross product means the curve
+    // bends counterclockwise, i.e. sweep-flag 0.
+    let bend = cross(start, end, mid);
+    let chord = distance(start, end);
+    let mut angle = 2.0 * (chord / (2.0 * circle.r)).min(1.0).asin();
+    if cross(start, end, center).signum() == bend.signum() {
+        // The center is on the same side as the curve: more than half a turn.
+        angle = 2.0 * std::f64::consts::PI - angle;
+    }
+
+    Some(ArcFit {
+        circle,
+        sweep: bend < 0.0,
+        angle,
+        deviation,
+    })
+}
+
+/// Distance from the middle of the chord to the middle of an arc of radius
+/// `r` spanning it.
+fn sagitta(r: f64, chord: f64, large_arc: bool) -> f64 {
+    let apothem = (r * r - chord * chord / 4.0).sqrt();
+    if large_arc {
+        r + apothem
+    } else {
+        r - apothem
+    }
+}
+
+/// The fitted radius is rarely round; use the shortest rounding of it that
+/// keeps the arc within `tolerance` of the curves.
+fn round_radius(r: f64, chord: f64, large_arc: bool, deviation: f64, tolerance: f64) -> f64 {
+    let exact = sagitta(r, chord, large_arc);
+    (0..6)
+        .map(|decimals| {
+            let scale = 10f64.powi(decimals);
+            (r * scale).round() / scale
+        })
+        .find(|&rounded| {
+            let moved = (sagitta(rounded, chord, large_arc) - exact).abs();
+            moved.is_finite() && deviation + moved <= tolerance
+        })
+        .unwrap_or(r)
+}
+
+/// Finds the longest run of cubics starting at `commands[index]` that lie on
+/// one circle and returns the equivalent arc and the index after the run.
+fn arc_for_curves(
+    commands: &[Command],
+    index: usize,
+    start: (f64, f64),
+    error: f64,
+) -> Option<(Command, usize)> {
+    let mut fits: Vec<ArcFit> = Vec::new();
+    let mut point = start;
+    let mut end = index;
+    while let Some(&Command::Curve(x1, y1, x2, y2, x, y)) = commands.get(end) {
+        let Some(fit) = fit_arc(point, [x1, y1, x2, y2, x, y], error) else {
+            break;
+        };
+        if let Some(first) = fits.first() {
+            let tolerance = arc_tolerance(first.circle.r, error);
+            let same_circle = distance(
+                (first.circle.cx, first.circle.cy),
+                (fit.circle.cx, fit.circle.cy),
+            ) <= tolerance
+                && (first.circle.r - fit.circle.r).abs() <= tolerance;
+            let total: f64 = fits.iter().map(|fit| fit.angle).sum();
+            // A single arc cannot draw a full circle: its ends would meet.
+            let full_turn = total + fit.angle >= 2.0 * std::f64::consts::PI
+                || distance((x, y), start) <= tolerance;
+            if fit.sweep != first.sweep || !same_circle || full_turn {
+                break;
+            }
+        }
+        fits.push(fit);
+        point = (x, y);
+        end += 1;
+    }
+
+    let first = *fits.first()?;
+    let Command::Curve(_, _, _, _, x, y) = commands[end - 1] else {
+        unreachable!("arc runs only contain curves");
+    };
+    let total: f64 = fits.iter().map(|fit| fit.angle).sum();
+    let large_arc = total > std::f64::consts::PI;
+    let deviation = fits.iter().map(|fit| fit.deviation).fold(0.0, f64::max);
+    let r = round_radius(
+        first.circle.r,
+        distance(start, (x, y)),
+        large_arc,
+        deviation,
+        arc_tolerance(first.circle.r, error),
+    );
+    let arc = Command::Arc(r, r, 0.0, large_arc, first.sweep, x, y);
+    Some((arc, end))
+}
+
+/// Replaces runs of cubic curves that trace a circular arc with a single
+/// `A` command when that is shorter.
+fn convert_curves_to_arcs(commands: Vec<Command>, p: usize) -> Vec<Command> {
+    let error = 10f64.powi(-(p as i32));
+    let mut out = Vec::with_capacity(commands.len());
+    let mut cur = (0.0, 0.0);
+    let mut subpath_start = (0.0, 0.0);
 
     let mut index = 0;
     while index < commands.len() {
-        if matches!(commands[index], Command::Move(_, _)) {
-            let mut end = index + 1;
-            while end < commands.len() && matches!(commands[end], Command::Line(_, _)) {
-                end += 1;
+        if let Some((arc, end)) = arc_for_curves(&commands, index, cur, error) {
+            let arc_len = command_len(&arc, cur, p);
+            let mut curves_len = 0;
+            let mut point = cur;
+            for cmd in &commands[index..end] {
+                curves_len += command_len(cmd, point, p);
+                point = end_point(cmd, point, subpath_start);
             }
-
-            if end > index + 1 {
-                let (fragment, x, y) = serialize_move_line_run(&commands[index..end], cur_x, cur_y, p);
-                s.push_str(&fragment);
-                cur_x = x;
-                cur_y = y;
-                if let Command::Move(move_x, move_y) = commands[index] {
-                    subpath_start_x = move_x;
-                    subpath_start_y = move_y;
-                }
+            if arc_len < curves_len {
+                cur = end_point(&arc, cur, subpath_start);
+                out.push(arc);
                 index = end;
                 continue;
             }
         }
 
         let cmd = &commands[index];
-        match cmd {
-            Command::Move(x, y) => {
-                s.push_str(&best_move_fragment(cur_x, cur_y, *x, *y, p));
-
-                cur_x = *x;
-                cur_y = *y;
-                subpath_start_x = *x;
-                subpath_start_y = *y;
-            }
-            Command::Line(x, y) => {
-                s.push_str(&best_line_fragment(cur_x, cur_y, *x, *y, p));
-                cur_x = *x;
-                cur_y = *y;
-            }
-            Command::Horiz(x) => {
-                // Just optimize to H/h
-                let abs_s = format!("H{}", format_num(*x, p));
-                let rel_s = format!("h{}", format_num(*x - cur_x, p));
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-            }
-            Command::Vert(y) => {
-                let abs_s = format!("V{}", format_num(*y, p));
-                let rel_s = format!("v{}", format_num(*y - cur_y, p));
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_y = *y;
-            }
-            Command::Close => {
-                s.push('z');
-                cur_x = subpath_start_x;
-                cur_y = subpath_start_y;
-            }
-            // ... Implement others (Curve, Quad, Arc) similar way
-            Command::Curve(x1, y1, x2, y2, x, y) => {
-                let abs_coords = format!(
-                    "{} {} {} {} {} {}",
-                    format_num(*x1, p),
-                    format_num(*y1, p),
-                    format_num(*x2, p),
-                    format_num(*y2, p),
-                    format_num(*x, p),
-                    format_num(*y, p)
-                );
-                let rel_coords = format!(
-                    "{} {} {} {} {} {}",
-                    format_num(*x1 - cur_x, p),
-                    format_num(*y1 - cur_y, p),
-                    format_num(*x2 - cur_x, p),
-                    format_num(*y2 - cur_y, p),
-                    format_num(*x - cur_x, p),
-                    format_num(*y - cur_y, p)
-                );
-
-                let abs_s = format!("C{}", abs_coords);
-                let rel_s = format!("c{}", rel_coords);
-
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-                cur_y = *y;
-            }
-            Command::SmoothCurve(x2, y2, x, y) => {
-                let abs_coords = format!(
-                    "{} {} {} {}",
-                    format_num(*x2, p),
-                    format_num(*y2, p),
-                    format_num(*x, p),
-                    format_num(*y, p)
-                );
-                let rel_coords = format!(
-                    "{} {} {} {}",
-                    format_num(*x2 - cur_x, p),
-                    format_num(*y2 - cur_y, p),
-                    format_num(*x - cur_x, p),
-                    format_num(*y - cur_y, p)
-                );
-
-                let abs_s = format!("S{}", abs_coords);
-                let rel_s = format!("s{}", rel_coords);
-
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-                cur_y = *y;
-            }
-            Command::Quad(x1, y1, x, y) => {
-                let abs_coords = format!(
-                    "{} {} {} {}",
-                    format_num(*x1, p),
-                    format_num(*y1, p),
-                    format_num(*x, p),
-                    format_num(*y, p)
-                );
-                let rel_coords = format!(
-                    "{} {} {} {}",
-                    format_num(*x1 - cur_x, p),
-                    format_num(*y1 - cur_y, p),
-                    format_num(*x - cur_x, p),
-                    format_num(*y - cur_y, p)
-                );
-
-                let abs_s = format!("Q{}", abs_coords);
-                let rel_s = format!("q{}", rel_coords);
-
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-                cur_y = *y;
-            }
-            Command::SmoothQuad(x, y) => {
-                let abs_coords = format!("{} {}", format_num(*x, p), format_num(*y, p));
-                let rel_coords = format!(
-                    "{} {}",
-                    format_num(*x - cur_x, p),
-                    format_num(*y - cur_y, p)
-                );
-
-                let abs_s = format!("T{}", abs_coords);
-                let rel_s = format!("t{}", rel_coords);
-
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-                cur_y = *y;
-            }
-            Command::Arc(rx, ry, rot, la, sf, x, y) => {
-                // Formatting arc flags: 0 or 1
-                let la_s = if *la { "1" } else { "0" };
-                let sf_s = if *sf { "1" } else { "0" };
-
-                let abs_coords = format!(
-                    "{} {} {} {} {} {} {}",
-                    format_num(*rx, p),
-                    format_num(*ry, p),
-                    format_num(*rot, p),
-                    la_s,
-                    sf_s,
-                    format_num(*x, p),
-                    format_num(*y, p)
-                );
-                let rel_coords = format!(
-                    "{} {} {} {} {} {} {}",
-                    format_num(*rx, p),
-                    format_num(*ry, p),
-                    format_num(*rot, p),
-                    la_s,
-                    sf_s,
-                    format_num(*x - cur_x, p),
-                    format_num(*y - cur_y, p)
-                );
-
-                let abs_s = format!("A{}", abs_coords);
-                let rel_s = format!("a{}", rel_coords);
-
-                if rel_s.len() <= abs_s.len() {
-                    s.push_str(&rel_s);
-                } else {
-                    s.push_str(&abs_s);
-                }
-                cur_x = *x;
-                cur_y = *y;
-            }
+        if let Command::Move(x, y) = *cmd {
+            subpath_start = (x, y);
         }
-
+        cur = end_point(cmd, cur, subpath_start);
+        out.push(cmd.clone());
         index += 1;
     }
 
-    s
+    out
 }
 
 // Reuse PathLexer from previous tool call (need to duplicate code here as I'm overwriting file)
@@ -840,7 +1260,7 @@ mod tests {
         // L 11 11 (7 chars)
         // l 1 1 (5 chars) -> Wins
         let input = "M 10 10 L 11 11";
-        let out = optimize_path_data(input, &ConvertPathData::default());
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
         assert_eq!(out, "M10 10l1 1");
     }
 
@@ -851,22 +1271,22 @@ mod tests {
         // l 10 0 (6)
         // H 20 / h 10 are both 3 chars, prefer relative on ties.
         let input = "M 10 10 L 20 10";
-        let out = optimize_path_data(input, &ConvertPathData::default());
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
         assert_eq!(out, "M10 10h10");
     }
 
     #[test]
     fn test_optimize_line_run_after_move() {
         let input = "M2 2L10 10L18 2";
-        let out = optimize_path_data(input, &ConvertPathData::default());
-        assert_eq!(out, "M2 2l8 8l8-8");
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
+        assert_eq!(out, "M2 2l8 8 8-8");
     }
 
     #[test]
     fn test_optimize_arc() {
         // Circle path from convert_shape_to_path
         let input = "M0 50A50 50 0 1 0 100 50A50 50 0 1 0 0 50z";
-        let out = optimize_path_data(input, &ConvertPathData::default());
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
         println!("Optimized Arc: '{}'", out);
         // Should not lose the arcs!
         assert!(
@@ -875,4 +1295,226 @@ mod tests {
             out
         );
     }
+
+    #[test]
+    fn test_make_arcs_quarter_circle() {
+        let input = "M5 0C5 2.761 2.761 5 0 5";
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
+        assert_eq!(out, "M5 0a5 5 0 0 1-5 5");
+
+        let opts = ConvertPathData {
+            no_space_after_flags: true,
+            ..ConvertPathData::default()
+        };
+        assert_eq!(optimize_path_data(input, &opts, false), "M5 0A5 5 0 010 5");
+
+        let opts = ConvertPathData {
+            make_arcs: false,
+            ..ConvertPathData::default()
+        };
+        assert_eq!(
+            optimize_path_data(input, &opts, false),
+            "M5 0c0 2.761-2.239 5-5 5"
+        );
+    }
+
+    #[test]
+    fn test_make_arcs_joins_runs_but_not_full_circles() {
+        let circle = "M5 0C5 2.761 2.761 5 0 5C-2.761 5-5 2.761-5 0\
+                      C-5-2.761-2.761-5 0-5C2.761-5 5-2.761 5 0z";
+        let out = optimize_path_data(circle, &ConvertPathData::default(), false);
+        assert_eq!(out, "M5 0a5.001 5.001 0 1 1-5-5 5 5 0 0 1 5 5z");
+    }
+
+    #[test]
+    fn test_make_arcs_keeps_other_curves() {
+        let input = "M0 0C10 0 10 10 20 20";
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0c10 0 10 10 20 20");
+
+        // The `S` continues the quarter circle into a half circle.
+        let input = "M5 0C5 2.761 2.761 5 0 5S-5 2.761-5 0";
+        let out = optimize_path_data(input, &ConvertPathData::default(), false);
+        assert_eq!(out, "M5 0A5.001 5.001 0 0 1-5 0");
+    }
+
+    #[test]
+    fn test_parse_positions() {
+        // `z` returns to the subpath start.
+        let out = optimize_path_data("M0 0L10 10z l5 5", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0l10 10zl5 5");
+        // Extra pairs after a moveto are line-tos.
+        let out = optimize_path_data("M0 0 10 10 20 0", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0l10 10L20 0");
+        // Malformed data stops the parse instead of looping forever.
+        let out = optimize_path_data("M0 0L#", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0");
+    }
+
+    #[test]
+    fn test_straighten_flat_curves() {
+        let out = optimize_path_data("M0 0C3 0 7 0 10 0", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0h10");
+        let out = optimize_path_data("M0 0Q5 .0004 10 0", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0h10");
+        // Control points beyond rounding distance.
+        let out = optimize_path_data("M0 0C3-1 7 1 10 0", &ConvertPathData::default(), false);
+        assert_eq!(out, "M0 0c3-1 7 1 10 0");
+    }
+
+    #[test]
+    fn test_shorthands() {
+        let opts = ConvertPathData::default();
+        let out = optimize_path_data("M0 0C0 5 5 10 10 10C15 10 20 5 20 0", &opts, false);
+        assert_eq!(out, "M0 0c0 5 5 10 10 10S20 5 20 0");
+        let out = optimize_path_data("M0 0Q5 10 10 0Q15-10 20 0Q25 10 30 0", &opts, false);
+        assert_eq!(out, "M0 0q5 10 10 0t10 0 10 0");
+        // Not a reflection.
+        let out = optimize_path_data("M0 0Q5 10 10 0Q15 10 20 0", &opts, false);
+        assert_eq!(out, "M0 0q5 10 10 0 5 10 10 0");
+    }
+
+    #[test]
+    fn test_shorthands_expand_when_predecessor_changes() {
+        let opts = ConvertPathData::default();
+        // The flat curve becomes a line, so the `S` needs its control point.
+        let out = optimize_path_data("M0 0C3 0 7 0 10 0S20 5 30 0", &opts, false);
+        assert_eq!(out, "M0 0h10c3 0 10 5 20 0");
+        // A zero-length curve between a curve and a `T` is dropped.
+        let out = optimize_path_data("M0 0Q5 10 10 0Q10 0 10 0T20 0", &opts, false);
+        assert_eq!(out, "M0 0q5 10 10 0h10");
+    }
+
+    #[test]
+    fn test_remove_useless_segments() {
+        let opts = ConvertPathData::default();
+        let out = optimize_path_data("M0 0L10 0L10 0C10 0 10 0 10 0L10 10", &opts, false);
+        assert_eq!(out, "M0 0h10v10");
+        let out = optimize_path_data("M0 0L10 0L10 10L0 0z", &opts, false);
+        assert_eq!(out, "M0 0h10v10z");
+        // A loop that returns to its start is not zero-length.
+        let out = optimize_path_data("M0 0C0 10 10 10 0 0", &opts, false);
+        assert_eq!(out, "M0 0c0 10 10 10 0 0");
+        // Dots stay for round caps.
+        let out = optimize_path_data("M5 5L5 5zM0 0L10 0L0 0z", &opts, false);
+        assert_eq!(out, "M5 5v0zM0 0h10z");
+    }
+
+    #[test]
+    fn test_markers_keep_segments() {
+        let input = "<svg><path d=\"M0 0L10 0L10 0L0 0z\" marker-mid=\"url(#m)\"/></svg>";
+        let mut doc = crate::parser::parse(input).unwrap();
+        ConvertPathData::default().apply(&mut doc);
+        assert_eq!(
+            crate::printer::print(&doc),
+            "<svg><path d=\"M0 0h10v0H0z\" marker-mid=\"url(#m)\"/></svg>"
+        );
+
+        let opts = ConvertPathData::default();
+        let d = "M5 0C5 2.761 2.761 5 0 5S-5 2.761-5 0";
+        assert_eq!(
+            optimize_path_data(d, &opts, true),
+            "M5 0c0 2.761-2.239 5-5 5s-5-2.239-5-5"
+        );
+    }
+
+    #[test]
+    fn test_inherited_and_styled_markers_keep_segments() {
+        let optimize = |input: &str| {
+            let mut doc = crate::parser::parse(input).unwrap();
+            ConvertPathData::default().apply(&mut doc);
+            crate::printer::print(&doc)
+        };
+        let half_circle = "M5 0C5 2.761 2.761 5 0 5C-2.761 5-5 2.761-5 0";
+
+        let out = optimize(&format!(
+            "<svg><g marker-mid=\"url(#m)\"><path d=\"{half_circle}\"/></g></svg>"
+        ));
+        assert!(
+            out.contains("d=\"M5 0c0 2.761-2.239 5-5 5s-5-2.239-5-5\""),
+            "{out}"
+        );
+
+        let out =
+            optimize("<svg><path d=\"M0 0L10 0L10 0L20 0\" style=\"marker-mid:url(#m)\"/></svg>");
+        assert!(out.contains("d=\"M0 0h10v0h10\""), "{out}");
+
+        let out = optimize(&format!(
+            "<svg><style>.m{{marker:url(#m)}}</style><path class=\"m\" d=\"{half_circle}\"/></svg>"
+        ));
+        assert!(
+            out.contains("d=\"M5 0c0 2.761-2.239 5-5 5s-5-2.239-5-5\""),
+            "{out}"
+        );
+
+        // Unrelated styles don't hold the optimization back.
+        let out = optimize(&format!(
+            "<svg><style>.m{{fill:red}}</style><path class=\"m\" d=\"{half_circle}\"/></svg>"
+        ));
+        assert!(out.contains("d=\"M5 0A5.001 5.001 0 0 1-5 0\""), "{out}");
+    }
+
+    #[test]
+    fn test_compact_serialization() {
+        let opts = ConvertPathData::default();
+        let cases = [
+            // `.5.5` needs no separator, `5 .5` does.
+            ("M.5 .5L10 .5", "M.5.5H10"),
+            ("M5 .5L.5 9", "M5 .5.5 9"),
+            // Rounded `-0` is written as `0`.
+            ("M0 0L10 -0.0001", "M0 0h10"),
+            // Repeated letters, including arcs; flags are only glued to a
+            // following minus sign.
+            (
+                "M0 0A5 5 0 0 1 10 0A5 5 0 0 1 20 0",
+                "M0 0a5 5 0 0 1 10 0 5 5 0 0 1 10 0",
+            ),
+        ];
+        for (input, expected) in cases {
+            let out = optimize_path_data(input, &opts, false);
+            assert_eq!(out, expected, "{input}");
+            // The compact form parses back to the same commands.
+            assert_eq!(parse_path_data(&out), parse_path_data(expected));
+        }
+
+        let input = "M0 0C1 2 3-4 5 6S7 8 9 10Q1 1 2 0T4 0A1 2 30 1 0 5 5z";
+        let out = optimize_path_data(input, &opts, false);
+        let reparsed = optimize_path_data(&out, &opts, false);
+        assert_eq!(out, reparsed);
+
+        let opts = ConvertPathData {
+            no_space_after_flags: true,
+            ..ConvertPathData::default()
+        };
+        let out = optimize_path_data("M0 0A5 5 0 0 1 10 0A5 5 0 0 1 20 0", &opts, false);
+        assert_eq!(out, "M0 0a5 5 0 0110 0 5 5 0 0110 0");
+        assert_eq!(optimize_path_data(&out, &opts, false), out);
+    }
+
+    #[test]
+    fn test_encodings_chosen_for_whole_path() {
+        // Greedily `l10 10` wins the tie with ` 11 11`, but then the last line
+        // needs its letter: `M1 1l10 10L-1-1`.
+        let out = optimize_path_data("M1 1L11 11L-1-1", &ConvertPathData::default(), false);
+        assert_eq!(out, "M1 1 11 11-1-1");
+    }
+
+    #[test]
+    fn test_relative_coordinates_do_not_drift() {
+        let opts = ConvertPathData {
+            float_precision: 0,
+            ..ConvertPathData::default()
+        };
+        let input = "M0 0L.6 10L1.2 20L1.8 30L2.4 40L3 50";
+        let out = optimize_path_data(input, &opts, false);
+        // Relative steps measured from the exact positions would all round
+        // to 1 and end at x=5.
+        let mut x = 0.0;
+        for cmd in parse_path_data(&out) {
+            if let Command::Line(end_x, _) = cmd {
+                x = end_x;
+            }
+        }
+        assert_eq!(x, 3.0, "{out}");
+    }
 }
@@ -113,7 +113,12 @@ fn convert_rect(elem: &Element) -> Option<String> {
     Some(path_data)
 }
 
-fn resolve_rect_radius(rx_raw: Option<f64>, ry_raw: Option<f64>, w: f64, h: f64) -> Option<(f64, f64)> {
+fn resolve_rect_radius(
+    rx_raw: Option<f64>,
+    ry_raw: Option<f64>,
+    w: f64,
+    h: f64,
+) -> Option<(f64, f64)> {
     let mut rx = match (rx_raw, ry_raw) {
         (Some(rx), _) => rx,
         (None, Some(ry)) => ry,
@@ -136,8 +141,13 @@ fn resolve_rect_radius(rx_raw: Option<f64>, ry_raw: Option<f64>, w: f64, h: f64)
 }
 
 fn serialized_present_shape_attrs_len(elem: &Element, attrs: &[&str]) -> usize {
-    attrs.iter()
-        .filter_map(|attr| elem.attributes.get(*attr).map(|value| serialized_attr_len(attr, value)))
+    attrs
+        .iter()
+        .filter_map(|attr| {
+            elem.attributes
+                .get(*attr)
+                .map(|value| serialized_attr_len(attr, value))
+        })
         .sum()
 }
 
@@ -183,7 +193,7 @@ fn convert_poly(elem: &Element, close: bool) -> Option<String> {
         // Rest
         for i in (2..coords.len()).step_by(2) {
             if i + 1 < coords.len() {
-                d.push_str("L");
+                d.push('L');
                 d.push_str(coords[i]);
                 d.push(' ');
                 d.push_str(coords[i + 1]);
@@ -265,7 +275,8 @@ mod tests {
 
     #[test]
     fn test_keep_rounded_rect_when_path_would_grow() {
-        let input = "<svg><rect x=\"10\" y=\"20\" width=\"100\" height=\"50\" rx=\"8\" ry=\"6\"/></svg>";
+        let input =
+            "<svg><rect x=\"10\" y=\"20\" width=\"100\" height=\"50\" rx=\"8\" ry=\"6\"/></svg>";
 
         let mut doc = parser::parse(input).unwrap();
         ConvertShapeToPath.apply(&mut doc);
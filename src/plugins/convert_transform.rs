@@ -225,7 +225,7 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
 
             match name.as_str() {
                 "translate" => {
-                    let tx = *args.get(0).unwrap_or(&0.0);
+                    let tx = *args.first().unwrap_or(&0.0);
                     let ty = *args.get(1).unwrap_or(&0.0);
                     matrices.push(Matrix {
                         a: 1.0,
@@ -237,7 +237,7 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
                     });
                 }
                 "scale" => {
-                    let sx = *args.get(0).unwrap_or(&1.0);
+                    let sx = *args.first().unwrap_or(&1.0);
                     let sy = *args.get(1).unwrap_or(&sx); // if 1 arg, scale(s, s)
                     matrices.push(Matrix {
                         a: sx,
@@ -249,7 +249,7 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
                     });
                 }
                 "rotate" => {
-                    let angle = *args.get(0).unwrap_or(&0.0);
+                    let angle = *args.first().unwrap_or(&0.0);
                     // cx, cy optional
                     let cx = *args.get(1).unwrap_or(&0.0);
                     let cy = *args.get(2).unwrap_or(&0.0);
@@ -291,7 +291,7 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
                     matrices.push(m);
                 }
                 "skewX" => {
-                    let a = *args.get(0).unwrap_or(&0.0);
+                    let a = *args.first().unwrap_or(&0.0);
                     let rad = a * PI / 180.0;
                     matrices.push(Matrix {
                         a: 1.0,
@@ -303,7 +303,7 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
                     });
                 }
                 "skewY" => {
-                    let a = *args.get(0).unwrap_or(&0.0);
+                    let a = *args.first().unwrap_or(&0.0);
                     let rad = a * PI / 180.0;
                     matrices.push(Matrix {
                         a: 1.0,
@@ -314,17 +314,15 @@ fn parse_transform_manual(s: &str) -> Vec<Matrix> {
                         f: 0.0,
                     });
                 }
-                "matrix" => {
-                    if args.len() == 6 {
-                        matrices.push(Matrix {
-                            a: args[0],
-                            b: args[1],
-                            c: args[2],
-                            d: args[3],
-                            e: args[4],
-                            f: args[5],
-                        });
-                    }
+                "matrix" if args.len() == 6 => {
+                    matrices.push(Matrix {
+                        a: args[0],
+                        b: args[1],
+                        c: args[2],
+                        d: args[3],
+                        e: args[4],
+                        f: args[5],
+                    });
                 }
                 _ => {}
             }
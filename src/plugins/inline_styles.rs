@@ -22,7 +22,7 @@ impl Plugin for InlineStyles {
 
         let matches = collect_selector_matches(&doc.root, &selectors);
 
-        let mut inline_actions: HashMap<Vec<usize>, Vec<Vec<(String, String)>>> = HashMap::new();
+        let mut inline_actions: InlineActions = HashMap::new();
         let mut style_updates: HashMap<Vec<usize>, Option<String>> = HashMap::new();
         let mut inlined_classes: HashMap<Vec<usize>, Vec<String>> = HashMap::new();
         let mut remaining_classes = HashSet::new();
@@ -93,6 +93,8 @@ enum SimpleSelector {
     TagId(String, String),
 }
 
+type InlineActions = HashMap<Vec<usize>, Vec<Vec<(String, String)>>>;
+
 struct StylePlan {
     path: Vec<usize>,
     rules: Vec<StyleRule>,
@@ -412,11 +414,7 @@ fn selector_class_name(selector: &SimpleSelector) -> Option<&String> {
     }
 }
 
-fn apply_inline_actions(
-    nodes: &mut Vec<Node>,
-    actions: &HashMap<Vec<usize>, Vec<Vec<(String, String)>>>,
-    path: &mut Vec<usize>,
-) {
+fn apply_inline_actions(nodes: &mut [Node], actions: &InlineActions, path: &mut Vec<usize>) {
     for (index, node) in nodes.iter_mut().enumerate() {
         path.push(index);
 
@@ -495,7 +493,7 @@ fn rewrite_style_elements(
 }
 
 fn remove_inlined_classes(
-    nodes: &mut Vec<Node>,
+    nodes: &mut [Node],
     inlined_classes: &HashMap<Vec<usize>, Vec<String>>,
     remaining_classes: &HashSet<String>,
     path: &mut Vec<usize>,
@@ -541,8 +539,7 @@ fn strip_class_names(
     if kept.is_empty() {
         elem.attributes.shift_remove("class");
     } else if kept.len() * 2 - 1 < class_attr.len() {
-        elem.attributes
-            .insert("class".to_string(), kept.join(" "));
+        elem.attributes.insert("class".to_string(), kept.join(" "));
     }
 }
 
@@ -687,7 +684,8 @@ mod tests {
 
     #[test]
     fn test_inline_grouped_simple_selectors() {
-        let input = "<svg><style>.a,.b{fill:red}</style><rect class=\"a\"/><circle class=\"b\"/></svg>";
+        let input =
+            "<svg><style>.a,.b{fill:red}</style><rect class=\"a\"/><circle class=\"b\"/></svg>";
         let expected = "<svg><rect style=\"fill:red\"/><circle style=\"fill:red\"/></svg>";
 
         let mut doc = parser::parse(input).unwrap();
@@ -708,7 +706,8 @@ mod tests {
 
     #[test]
     fn test_inline_tag_class_selector() {
-        let input = "<svg><style>rect.a{fill:red}</style><rect class=\"a\"/><circle class=\"a\"/></svg>";
+        let input =
+            "<svg><style>rect.a{fill:red}</style><rect class=\"a\"/><circle class=\"a\"/></svg>";
         let expected = "<svg><rect style=\"fill:red\"/><circle class=\"a\"/></svg>";
 
         let mut doc = parser::parse(input).unwrap();
@@ -718,7 +717,8 @@ mod tests {
 
     #[test]
     fn test_inline_tag_id_selector() {
-        let input = "<svg><style>rect#hero{fill:red}</style><rect id=\"hero\"/><circle id=\"hero\"/></svg>";
+        let input =
+            "<svg><style>rect#hero{fill:red}</style><rect id=\"hero\"/><circle id=\"hero\"/></svg>";
         let expected = "<svg><rect id=\"hero\" style=\"fill:red\"/><circle id=\"hero\"/></svg>";
 
         let mut doc = parser::parse(input).unwrap();
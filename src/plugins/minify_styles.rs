@@ -130,7 +130,9 @@ fn strip_css_comments(css: &str) -> String {
     out
 }
 
-fn parse_stylesheet(css: &str) -> Option<Vec<(String, Vec<(String, String)>)>> {
+type StyleRule = (String, Vec<(String, String)>);
+
+fn parse_stylesheet(css: &str) -> Option<Vec<StyleRule>> {
     let bytes = css.as_bytes();
     let mut rules = Vec::new();
     let mut cursor = 0usize;
@@ -1,89 +1,60 @@
 use crate::plugins::Plugin;
-use crate::tree::{Document, Node};
-use std::collections::HashSet;
+use crate::tree::{Document, Element, Node};
+
+/// Known editor namespaces
+const EDITOR_NAMESPACES: &[&str] = &[
+    "http://www.inkscape.org/namespaces/inkscape",
+    "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
+    "http://ns.adobe.com/AdobeIllustrator/10.0/",
+    "http://ns.adobe.com/SaveForWeb/1.0/",
+];
 
 pub struct RemoveEditorsNSData;
 
 impl Plugin for RemoveEditorsNSData {
     fn apply(&self, doc: &mut Document) {
-        // Known editor namespaces
-        let editor_namespaces = [
-            "http://www.inkscape.org/namespaces/inkscape",
-            "http://sodipodi.sourceforge.net/DTD/sodipodi-0.dtd",
-            "http://ns.adobe.com/AdobeIllustrator/10.0/",
-            "http://ns.adobe.com/SaveForWeb/1.0/",
-        ];
-
-        // 1. Find prefixes bound to these namespaces (usually on root, but could be anywhere.
-        //    For simplicity, we verify root attributes for now as most editors put them there).
-        //    A robust implementation would track scope, but global removal is usually safe for optimization.
-        let mut prefixes_to_remove = HashSet::new();
-
-        // Helper to scan for xmlns definitions
-        fn scan_prefixes(nodes: &[Node], prefixes: &mut HashSet<String>, ns_list: &[&str]) {
-            for node in nodes {
-                if let Node::Element(elem) = node {
-                    for (k, v) in &elem.attributes {
-                        if k.starts_with("xmlns:") {
-                            let prefix = &k[6..];
-                            if ns_list.contains(&v.as_str()) {
-                                prefixes.insert(prefix.to_string());
-                            }
-                        }
-                    }
-                    scan_prefixes(&elem.children, prefixes, ns_list);
-                }
-            }
-        }
-
-        scan_prefixes(&doc.root, &mut prefixes_to_remove, &editor_namespaces);
-
-        // 2. Remove matching xmlns attributes and Namespaced elements/attributes
-        remove_ns_data(&mut doc.root, &prefixes_to_remove, &editor_namespaces);
+        // Namespaces are resolved per element, so a prefix only counts as
+        // editor data where it is actually bound to an editor namespace.
+        remove_ns_data(&mut doc.root);
     }
 }
 
-fn remove_ns_data(nodes: &mut Vec<Node>, prefixes: &HashSet<String>, ns_list: &[&str]) {
-    // Remove elements with matching prefix
-    nodes.retain(|node| {
-        if let Node::Element(elem) = node {
-            if let Some((prefix, _)) = elem.name.split_once(':') {
-                if prefixes.contains(prefix) {
-                    return false;
-                }
-            }
-        }
-        true
+fn is_editor_namespace(uri: Option<&str>) -> bool {
+    uri.is_some_and(|uri| EDITOR_NAMESPACES.contains(&uri))
+}
+
+fn remove_ns_data(nodes: &mut Vec<Node>) {
+    // Remove elements in an editor namespace
+    nodes.retain(|node| match node {
+        Node::Element(elem) => !is_editor_namespace(elem.namespace_uri()),
+        _ => true,
     });
 
     for node in nodes {
         if let Node::Element(elem) = node {
-            // Remove attributes
-            elem.attributes.retain(|k, v| {
-                // Remove xmlns:prefix="EDITOR_URI"
-                if k.starts_with("xmlns:") {
-                    let prefix = &k[6..];
-                    if prefixes.contains(prefix) && ns_list.contains(&v.as_str()) {
-                        return false;
-                    }
-                }
-
-                // Remove prefix:attr
-                if let Some((prefix, _)) = k.split_once(':') {
-                    if prefixes.contains(prefix) {
-                        return false;
-                    }
-                }
-
-                true
-            });
-
-            // Recurse
-            remove_ns_data(&mut elem.children, prefixes, ns_list);
+            remove_attributes(elem);
+            remove_ns_data(&mut elem.children);
         }
     }
 }
 
+fn remove_attributes(elem: &mut Element) {
+    let to_remove: Vec<String> = elem
+        .attributes
+        .iter()
+        .filter(|(key, value)| {
+            // xmlns:prefix="EDITOR_URI" and prefix:attr
+            (key.starts_with("xmlns:") && EDITOR_NAMESPACES.contains(&value.as_str()))
+                || is_editor_namespace(elem.attribute_namespace_uri(key))
+        })
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    for key in to_remove {
+        elem.attributes.shift_remove(&key);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,4 +82,14 @@ mod tests {
         let output = printer::print(&doc);
         assert_eq!(output, expected);
     }
+
+    #[test]
+    fn test_keeps_rebound_prefix() {
+        let input = r#"<svg xmlns:inkscape="http://www.inkscape.org/namespaces/inkscape"><g xmlns:inkscape="urn:app" inkscape:keep="1"><inkscape:data/></g><rect inkscape:label="x"/></svg>"#;
+        let expected = r#"<svg><g xmlns:inkscape="urn:app" inkscape:keep="1"><inkscape:data/></g><rect/></svg>"#;
+
+        let mut doc = parser::parse(input).unwrap();
+        RemoveEditorsNSData.apply(&mut doc);
+        assert_eq!(printer::print(&doc), expected);
+    }
 }
@@ -1,70 +1,55 @@
 use crate::plugins::Plugin;
-use crate::tree::{Document, Node};
-use std::collections::HashSet;
+use crate::tree::{split_qname, Document, Element, Node};
 
 pub struct RemoveUnusedNS;
 
 impl Plugin for RemoveUnusedNS {
     fn apply(&self, doc: &mut Document) {
-        // 1. Collect used namespaces
-        let mut used_prefixes = HashSet::new();
-        collect_used_prefixes(&doc.root, &mut used_prefixes);
-
-        // 2. Filter root attributes
-        process_root(&mut doc.root, &used_prefixes);
+        process_nodes(&mut doc.root);
     }
 }
 
-fn collect_used_prefixes(nodes: &[Node], used: &mut HashSet<String>) {
+fn process_nodes(nodes: &mut [Node]) {
     for node in nodes {
         if let Node::Element(elem) = node {
-            // Check element name (e.g. "svg:rect" -> "svg")
-            if let Some((prefix, _)) = elem.name.split_once(':') {
-                used.insert(prefix.to_string());
-            }
-
-            // Check attributes (e.g. "xlink:href" -> "xlink")
-            for (key, _) in &elem.attributes {
-                if let Some((prefix, _)) = key.split_once(':') {
-                    // xml:space etc. "xml" is visible
-                    used.insert(prefix.to_string());
-                }
+            // "xmlns" default namespace is special, we keep it.
+            // Only "xmlns:prefix" declarations that nothing in scope uses go.
+            let unused: Vec<String> = elem
+                .attributes
+                .keys()
+                .filter(|key| {
+                    key.strip_prefix("xmlns:")
+                        .is_some_and(|prefix| !uses_prefix(elem, prefix))
+                })
+                .cloned()
+                .collect();
+
+            for key in unused {
+                elem.attributes.shift_remove(&key);
             }
 
-            collect_used_prefixes(&elem.children, used);
+            process_nodes(&mut elem.children);
         }
     }
 }
 
-fn process_root(nodes: &mut Vec<Node>, used: &HashSet<String>) {
-    // Usually namespaces are defined on the first element (root svg)
-    // But technically can be anywhere. Simplifying to check all elements for xmlns definition.
-
-    for node in nodes {
-        if let Node::Element(elem) = node {
-            // Retain only used xmlns
-            // "xmlns" default namespace is special, we usually allow it if it's SVG
-            // "xmlns:prefix"
-
-            let mut to_remove = Vec::new();
-
-            for key in elem.attributes.keys() {
-                if key.starts_with("xmlns:") {
-                    let prefix = &key[6..];
-                    if !used.contains(prefix) {
-                        to_remove.push(key.clone());
-                    }
-                }
-            }
-
-            for k in to_remove {
-                elem.attributes.shift_remove(&k);
+/// Whether `elem` or a descendant that still sees the declaration on `elem`
+/// uses `prefix` in an element or attribute name.
+fn uses_prefix(elem: &Element, prefix: &str) -> bool {
+    let used_here = elem.prefix() == Some(prefix)
+        || elem
+            .attributes
+            .keys()
+            .any(|key| split_qname(key).0 == Some(prefix));
+
+    used_here
+        || elem.children.iter().any(|child| match child {
+            Node::Element(child) => {
+                let redeclared = child.attributes.contains_key(&format!("xmlns:{prefix}"));
+                !redeclared && uses_prefix(child, prefix)
             }
-
-            // Recurse? Though unlikely to have nested xmlns definitions in optimized SVG
-            process_root(&mut elem.children, used);
-        }
-    }
+            _ => false,
+        })
 }
 
 #[cfg(test)]
@@ -91,4 +76,14 @@ mod tests {
         RemoveUnusedNS.apply(&mut doc);
         assert_eq!(printer::print(&doc), input);
     }
+
+    #[test]
+    fn test_respects_redeclaration() {
+        let input = r##"<svg xmlns:a="urn:a"><g xmlns:a="urn:b"><use a:href="#id"/></g></svg>"##;
+        let expected = r##"<svg><g xmlns:a="urn:b"><use a:href="#id"/></g></svg>"##;
+
+        let mut doc = parser::parse(input).unwrap();
+        RemoveUnusedNS.apply(&mut doc);
+        assert_eq!(printer::print(&doc), expected);
+    }
 }
@@ -16,14 +16,11 @@ fn process_nodes(nodes: &mut Vec<Node>) {
                 elem.name.as_str(),
                 "rect" | "circle" | "ellipse" | "line" | "polygon" | "polyline" | "path"
             );
-            let stroke_disabled =
-                is_explicit_none(elem, "stroke") || has_zero_stroke_width(elem);
+            let stroke_disabled = is_explicit_none(elem, "stroke") || has_zero_stroke_width(elem);
             let fill_disabled = is_explicit_none(elem, "fill");
 
-            if is_shape && stroke_disabled && fill_disabled {
-                if !elem.attributes.contains_key("id") {
-                    return false;
-                }
+            if is_shape && stroke_disabled && fill_disabled && !elem.attributes.contains_key("id") {
+                return false;
             }
         }
         true
@@ -60,13 +57,15 @@ fn cleanup_paint_attrs(elem: &mut crate::tree::Element) {
 }
 
 fn is_explicit_none(elem: &crate::tree::Element, attr: &str) -> bool {
-    elem.attributes.get(attr).is_some_and(|value| value == "none")
+    elem.attributes
+        .get(attr)
+        .is_some_and(|value| value == "none")
 }
 
 fn has_zero_stroke_width(elem: &crate::tree::Element) -> bool {
-    elem.attributes.get("stroke-width").is_some_and(|value| {
-        matches!(value.trim(), "0" | "0px" | "0.0" | "0.0px")
-    })
+    elem.attributes
+        .get("stroke-width")
+        .is_some_and(|value| matches!(value.trim(), "0" | "0px" | "0.0" | "0.0px"))
 }
 
 fn remove_attrs(elem: &mut crate::tree::Element, attrs: &[&str]) {
@@ -1,4 +1,4 @@
-use crate::tree::{Document, Node};
+use crate::tree::{Document, Node, ENTITY_REF_END, ENTITY_REF_START};
 
 pub fn print(doc: &Document) -> String {
     let mut out = String::new();
@@ -25,7 +25,7 @@ fn print_node(node: &Node, out: &mut String) {
                 out.push(' ');
                 out.push_str(k);
                 out.push_str("=\"");
-                out.push_str(v);
+                escape_attribute(v, out);
                 out.push('"');
             }
 
@@ -42,7 +42,7 @@ fn print_node(node: &Node, out: &mut String) {
             }
         }
         Node::Text(text) => {
-            out.push_str(text);
+            escape_text(text, out);
         }
         Node::Comment(text) => {
             out.push_str("<!--");
@@ -66,7 +66,7 @@ fn print_node(node: &Node, out: &mut String) {
         Node::Doctype(text) => {
             out.push_str("<!DOCTYPE ");
             out.push_str(text);
-            out.push_str(">");
+            out.push('>');
         }
     }
 }
@@ -81,7 +81,7 @@ fn print_node_pretty(node: &Node, out: &mut String, indent: usize) {
                 out.push(' ');
                 out.push_str(k);
                 out.push_str("=\"");
-                out.push_str(v);
+                escape_attribute(v, out);
                 out.push('"');
             }
 
@@ -112,7 +112,7 @@ fn print_node_pretty(node: &Node, out: &mut String, indent: usize) {
         }
         Node::Text(text) => {
             write_indent(out, indent);
-            out.push_str(text);
+            escape_text(text, out);
             out.push('\n');
         }
         Node::Comment(text) => {
@@ -146,6 +146,50 @@ fn print_node_pretty(node: &Node, out: &mut String, indent: usize) {
     }
 }
 
+fn escape_attribute(value: &str, out: &mut String) {
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            ENTITY_REF_START => write_entity_ref(&mut chars, out),
+            '<' => out.push_str("&lt;"),
+            '"' => out.push_str("&quot;"),
+            // Literal whitespace would be normalized to spaces on the next parse.
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+fn escape_text(text: &str, out: &mut String) {
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            ENTITY_REF_START => write_entity_ref(&mut chars, out),
+            '<' => out.push_str("&lt;"),
+            // `>` only needs escaping where it would close a `]]>` sequence.
+            '>' if out.ends_with("]]") => out.push_str("&gt;"),
+            _ => out.push(c),
+        }
+    }
+}
+
+/// Writes back a reference the parser kept for an undeclared entity; see
+/// [`ENTITY_REF_START`].
+fn write_entity_ref(chars: &mut std::str::Chars, out: &mut String) {
+    let name: String = chars.take_while(|&c| c != ENTITY_REF_END).collect();
+    if name.is_empty() {
+        out.push_str("&#xFDD0;");
+    } else {
+        out.push('&');
+        out.push_str(&name);
+        out.push(';');
+    }
+}
+
 fn has_only_text_children(children: &[Node]) -> bool {
     children.iter().all(|child| matches!(child, Node::Text(_)))
 }
@@ -170,6 +214,19 @@ mod tests {
         assert_eq!(print_pretty(&doc), expected);
     }
 
+    #[test]
+    fn test_print_escapes_markup_characters() {
+        let input = "<svg><text title=\"&quot;a&quot; &amp; &lt;b&gt;\">x &lt; y &amp;&amp; ]]&gt; a > b</text></svg>";
+        let doc = parser::parse(input).unwrap();
+
+        let output = print(&doc);
+        assert_eq!(
+            output,
+            "<svg><text title=\"&quot;a&quot; &amp; &lt;b>\">x &lt; y &amp;&amp; ]]&gt; a > b</text></svg>"
+        );
+        assert_eq!(parser::parse(&output).unwrap().root, doc.root);
+    }
+
     #[test]
     fn test_print_pretty_keeps_text_inline() {
         let input = "<svg><text>Hello</text></svg>";
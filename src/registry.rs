@@ -0,0 +1,247 @@
+//! Registration of third-party plugins into the standard pipeline.
+//!
+//! ```
+//! use svgtidy::plugins::Plugin;
+//! use svgtidy::registry::{register_plugin, PluginPosition};
+//! use svgtidy::tree::Document;
+//!
+//! struct StripDataAttrs;
+//!
+//! impl Plugin for StripDataAttrs {
+//!     fn apply(&self, doc: &mut Document) {
+//!         // ...
+//!     }
+//! }
+//!
+//! register_plugin(
+//!     "stripDataAttrs",
+//!     true,
+//!     PluginPosition::After("cleanupAttrs".to_string()),
+//!     |_options| Box::new(StripDataAttrs),
+//! )
+//! .unwrap();
+//! ```
+
+use crate::error::{Error, RegistrationConflict, Result};
+use crate::pipeline::{plugin_descriptors, OptimizeOptions};
+use crate::plugins::Plugin;
+use std::sync::{Arc, RwLock};
+
+/// Builds a fresh plugin instance for one pipeline run.
+pub type PluginFactory = dyn Fn(&OptimizeOptions) -> Box<dyn Plugin> + Send + Sync;
+
+/// Where a registered plugin runs in the default plugin order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PluginPosition {
+    Start,
+    End,
+    Before(String),
+    After(String),
+}
+
+#[derive(Clone)]
+pub struct RegisteredPlugin {
+    pub name: String,
+    pub enabled_by_default: bool,
+    pub position: PluginPosition,
+    pub factory: Arc<PluginFactory>,
+}
+
+static REGISTRY: RwLock<Vec<RegisteredPlugin>> = RwLock::new(Vec::new());
+
+/// Registers an external plugin under `name`.
+///
+/// The name must not clash with a built-in or an already registered plugin,
+/// and `Before`/`After` positions must reference a built-in plugin; otherwise
+/// this returns [`Error::PluginRegistration`].
+pub fn register_plugin<F>(
+    name: impl Into<String>,
+    enabled_by_default: bool,
+    position: PluginPosition,
+    factory: F,
+) -> Result<()>
+where
+    F: Fn(&OptimizeOptions) -> Box<dyn Plugin> + Send + Sync + 'static,
+{
+    let name = name.into();
+    let conflict = |reason| Error::PluginRegistration {
+        name: name.clone(),
+        reason,
+    };
+    if is_builtin(&name) {
+        return Err(conflict(RegistrationConflict::Builtin));
+    }
+    if let PluginPosition::Before(anchor) | PluginPosition::After(anchor) = &position {
+        if !is_builtin(anchor) {
+            return Err(conflict(RegistrationConflict::UnknownAnchor(
+                anchor.clone(),
+            )));
+        }
+    }
+
+    let mut registry = REGISTRY.write().unwrap();
+    if registry.iter().any(|plugin| plugin.name == name) {
+        return Err(conflict(RegistrationConflict::AlreadyRegistered));
+    }
+    registry.push(RegisteredPlugin {
+        name,
+        enabled_by_default,
+        position,
+        factory: Arc::new(factory),
+    });
+    Ok(())
+}
+
+/// Removes a registered plugin. Returns `false` if no plugin had that name.
+pub fn unregister_plugin(name: &str) -> bool {
+    let mut registry = REGISTRY.write().unwrap();
+    let before = registry.len();
+    registry.retain(|plugin| plugin.name != name);
+    registry.len() != before
+}
+
+pub fn registered_plugins() -> Vec<RegisteredPlugin> {
+    REGISTRY.read().unwrap().clone()
+}
+
+pub fn find_registered_plugin(name: &str) -> Option<RegisteredPlugin> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|plugin| plugin.name == name)
+        .cloned()
+}
+
+fn is_builtin(name: &str) -> bool {
+    plugin_descriptors()
+        .iter()
+        .any(|descriptor| descriptor.name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::{apply_default_pipeline, unknown_plugin_names};
+    use crate::tree::{Document, Node};
+    use crate::{parser, printer};
+
+    // The registry is global and other tests run the default pipeline at the
+    // same time, so these tests register their plugins disabled by default,
+    // enable them explicitly, and only touch documents they own.
+    struct AddComment;
+
+    impl Plugin for AddComment {
+        fn apply(&self, doc: &mut Document) {
+            if let Some(Node::Element(root)) = doc.root.first_mut() {
+                if root.name == "registryTest" {
+                    root.children.push(Node::Comment("added".to_string()));
+                }
+            }
+        }
+    }
+
+    fn run(options: &OptimizeOptions) -> String {
+        let mut doc = parser::parse("<registryTest/>").unwrap();
+        apply_default_pipeline(&mut doc, options).unwrap();
+        printer::print(&doc)
+    }
+
+    #[test]
+    fn test_registered_plugin_runs_at_position() {
+        register_plugin(
+            "testCommentBefore",
+            false,
+            PluginPosition::Before("removeComments".to_string()),
+            |_| Box::new(AddComment),
+        )
+        .unwrap();
+        let mut options = OptimizeOptions::default();
+        options.enable.insert("testCommentBefore".to_string());
+        let before = run(&options);
+
+        unregister_plugin("testCommentBefore");
+        register_plugin(
+            "testCommentAfter",
+            false,
+            PluginPosition::After("removeComments".to_string()),
+            |_| Box::new(AddComment),
+        )
+        .unwrap();
+        options.enable.clear();
+        options.enable.insert("testCommentAfter".to_string());
+        let after = run(&options);
+
+        options.disable.insert("testCommentAfter".to_string());
+        let disabled = run(&options);
+        unregister_plugin("testCommentAfter");
+
+        assert_eq!(before, "<registryTest/>");
+        assert_eq!(after, "<registryTest><!--added--></registryTest>");
+        assert_eq!(disabled, "<registryTest/>");
+    }
+
+    #[test]
+    fn test_registered_plugin_disabled_by_default() {
+        register_plugin("testOptIn", false, PluginPosition::End, |_| {
+            Box::new(AddComment)
+        })
+        .unwrap();
+
+        let mut options = OptimizeOptions::default();
+        let default_output = run(&options);
+        options.enable.insert("testOptIn".to_string());
+        let enabled_output = run(&options);
+        let unknown = unknown_plugin_names(&options);
+        unregister_plugin("testOptIn");
+
+        assert_eq!(default_output, "<registryTest/>");
+        assert_eq!(enabled_output, "<registryTest><!--added--></registryTest>");
+        assert!(unknown.is_empty());
+        assert_eq!(
+            unknown_plugin_names(&options),
+            vec!["testOptIn".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_register_plugin_rejects_conflicts() {
+        let builtin = register_plugin("removeComments", true, PluginPosition::End, |_| {
+            Box::new(AddComment)
+        });
+        assert!(matches!(
+            builtin,
+            Err(Error::PluginRegistration {
+                reason: RegistrationConflict::Builtin,
+                ..
+            })
+        ));
+        let bad_anchor = register_plugin(
+            "testBadAnchor",
+            false,
+            PluginPosition::After("noSuchPlugin".to_string()),
+            |_| Box::new(AddComment),
+        );
+        assert!(matches!(
+            bad_anchor,
+            Err(Error::PluginRegistration {
+                reason: RegistrationConflict::UnknownAnchor(anchor),
+                ..
+            }) if anchor == "noSuchPlugin"
+        ));
+
+        register_plugin("testDuplicate", false, PluginPosition::End, |_| {
+            Box::new(AddComment)
+        })
+        .unwrap();
+        let duplicate = register_plugin("testDuplicate", false, PluginPosition::End, |_| {
+            Box::new(AddComment)
+        });
+        unregister_plugin("testDuplicate");
+
+        assert_eq!(
+            duplicate.unwrap_err().to_string(),
+            "plugin `testDuplicate` is already registered"
+        );
+    }
+}
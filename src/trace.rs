@@ -0,0 +1,533 @@
+//! Records what each plugin changed during a pipeline run.
+//!
+//! ```
+//! use svgtidy::pipeline::OptimizeOptions;
+//! use svgtidy::trace::optimize_with_trace;
+//!
+//! let (output, trace) =
+//!     optimize_with_trace("<svg><!-- note --><rect/></svg>", &OptimizeOptions::default()).unwrap();
+//! let step = trace.steps.iter().find(|step| step.plugin == "removeComments").unwrap();
+//! assert_eq!(step.bytes_saved(), 13);
+//! assert_eq!(step.edits[0].to_string(), "removed comment \" note \" from <svg> (1:1)");
+//! # let _ = output;
+//! ```
+//!
+//! Elements are matched across a plugin run by their position in the
+//! original source, so elements that plugins create are reported as added.
+
+use crate::error::Result;
+use crate::pipeline::{OptimizeOptions, PipelineObserver};
+use crate::printer;
+use crate::tree::{Document, Element, Node, SourceSpan};
+use indexmap::IndexMap;
+use std::fmt;
+
+/// Longest attribute value or text shown in an [`Edit`] description.
+const PREVIEW_CHARS: usize = 40;
+
+/// Optimizes `svg` like [`crate::optimize_with_options`], recording each
+/// plugin that ran.
+pub fn optimize_with_trace(svg: &str, options: &OptimizeOptions) -> Result<(String, Trace)> {
+    let (doc, trace) = optimize_to_document_with_trace(svg, options)?;
+    Ok((printer::print(&doc), trace))
+}
+
+pub fn optimize_to_document_with_trace(
+    svg: &str,
+    options: &OptimizeOptions,
+) -> Result<(Document, Trace)> {
+    let mut tracer = Tracer::default();
+    let doc = crate::optimize_to_document_with_observer(svg, options, &mut tracer)?;
+    Ok((doc, tracer.trace))
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    pub steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    /// Steps that changed the document.
+    pub fn changes(&self) -> impl Iterator<Item = &TraceStep> {
+        self.steps.iter().filter(|step| step.changed())
+    }
+}
+
+/// One plugin run. Sizes are of the minified output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceStep {
+    pub plugin: String,
+    pub pass: usize,
+    pub size_before: usize,
+    pub size_after: usize,
+    pub edits: Vec<Edit>,
+    /// The pass grew the output in multipass mode and was undone.
+    pub reverted: bool,
+}
+
+impl TraceStep {
+    /// Negative when the plugin made the output larger.
+    pub fn bytes_saved(&self) -> isize {
+        self.size_before as isize - self.size_after as isize
+    }
+
+    pub fn changed(&self) -> bool {
+        !self.edits.is_empty() || self.size_before != self.size_after
+    }
+}
+
+/// An element, named by its tag and original source position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ElementRef {
+    pub name: String,
+    pub span: Option<SourceSpan>,
+}
+
+impl ElementRef {
+    fn new(element: &Element) -> Self {
+        Self {
+            name: element.name.clone(),
+            span: element.span,
+        }
+    }
+}
+
+impl fmt::Display for ElementRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.name)?;
+        if let Some(span) = self.span {
+            write!(f, " ({}:{})", span.line, span.column)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    Text,
+    Comment,
+    Cdata,
+    Doctype,
+    ProcessingInstruction,
+}
+
+impl fmt::Display for NodeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Text => "text",
+            Self::Comment => "comment",
+            Self::Cdata => "CDATA",
+            Self::Doctype => "doctype",
+            Self::ProcessingInstruction => "processing instruction",
+        })
+    }
+}
+
+/// A single change made by a plugin. `parent: None` is the document itself.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    ElementRemoved {
+        element: ElementRef,
+    },
+    ElementAdded {
+        element: ElementRef,
+        parent: Option<ElementRef>,
+    },
+    ElementRenamed {
+        element: ElementRef,
+        name: String,
+    },
+    ElementMoved {
+        element: ElementRef,
+        from: Option<ElementRef>,
+        to: Option<ElementRef>,
+    },
+    AttributeRemoved {
+        element: ElementRef,
+        name: String,
+        value: String,
+    },
+    AttributeAdded {
+        element: ElementRef,
+        name: String,
+        value: String,
+    },
+    AttributeChanged {
+        element: ElementRef,
+        name: String,
+        old: String,
+        new: String,
+    },
+    NodeRemoved {
+        parent: Option<ElementRef>,
+        kind: NodeKind,
+        content: String,
+    },
+    NodeAdded {
+        parent: Option<ElementRef>,
+        kind: NodeKind,
+        content: String,
+    },
+}
+
+struct Parent<'a>(&'a Option<ElementRef>);
+
+impl fmt::Display for Parent<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(element) => element.fmt(f),
+            None => f.write_str("the document"),
+        }
+    }
+}
+
+struct Preview<'a>(&'a str);
+
+impl fmt::Display for Preview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut chars = self.0.chars();
+        let shown: String = chars.by_ref().take(PREVIEW_CHARS).collect();
+        let ellipsis = if chars.next().is_some() { "..." } else { "" };
+        write!(f, "{:?}", format!("{shown}{ellipsis}"))
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ElementRemoved { element } => write!(f, "removed {element}"),
+            Self::ElementAdded { element, parent } => {
+                write!(f, "added {element} to {}", Parent(parent))
+            }
+            Self::ElementRenamed { element, name } => write!(f, "renamed {element} to <{name}>"),
+            Self::ElementMoved { element, from, to } => {
+                write!(f, "moved {element} from {} to {}", Parent(from), Parent(to))
+            }
+            Self::AttributeRemoved {
+                element,
+                name,
+                value,
+            } => write!(f, "removed {name}={} from {element}", Preview(value)),
+            Self::AttributeAdded {
+                element,
+                name,
+                value,
+            } => write!(f, "added {name}={} to {element}", Preview(value)),
+            Self::AttributeChanged {
+                element,
+                name,
+                old,
+                new,
+            } => write!(
+                f,
+                "rewrote {name} on {element}: {} -> {}",
+                Preview(old),
+                Preview(new)
+            ),
+            Self::NodeRemoved {
+                parent,
+                kind,
+                content,
+            } => write!(
+                f,
+                "removed {kind} {} from {}",
+                Preview(content),
+                Parent(parent)
+            ),
+            Self::NodeAdded {
+                parent,
+                kind,
+                content,
+            } => write!(f, "added {kind} {} to {}", Preview(content), Parent(parent)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tracer {
+    trace: Trace,
+    before: Option<(Document, usize)>,
+}
+
+impl PipelineObserver for Tracer {
+    fn before_plugin(&mut self, _pass: usize, _name: &str, doc: &Document) {
+        self.before = Some((doc.clone(), printer::print(doc).len()));
+    }
+
+    fn after_plugin(&mut self, pass: usize, name: &str, doc: &Document) {
+        let Some((before, size_before)) = self.before.take() else {
+            return;
+        };
+        self.trace.steps.push(TraceStep {
+            plugin: name.to_string(),
+            pass,
+            size_before,
+            size_after: printer::print(doc).len(),
+            edits: diff_documents(&before, doc),
+            reverted: false,
+        });
+    }
+
+    fn reverted_pass(&mut self, pass: usize) {
+        for step in &mut self.trace.steps {
+            if step.pass == pass {
+                step.reverted = true;
+            }
+        }
+    }
+}
+
+/// Identifies an element across plugin runs: parsed elements by where they
+/// started in the source, others by their position in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    Source(usize),
+    Path(Vec<usize>),
+}
+
+struct Entry<'a> {
+    element: &'a Element,
+    parent: Option<Key>,
+}
+
+#[derive(Default)]
+struct Snapshot<'a> {
+    elements: IndexMap<Key, Entry<'a>>,
+    /// Non-element children per parent (`None` is the document).
+    nodes: IndexMap<Option<Key>, Vec<(NodeKind, &'a str)>>,
+}
+
+impl<'a> Snapshot<'a> {
+    fn new(doc: &'a Document) -> Self {
+        let mut snapshot = Self::default();
+        snapshot.collect(&doc.root, None, &mut Vec::new());
+        snapshot
+    }
+
+    fn collect(&mut self, nodes: &'a [Node], parent: Option<Key>, path: &mut Vec<usize>) {
+        let others = self.nodes.entry(parent.clone()).or_default();
+        for node in nodes {
+            let other = match node {
+                Node::Element(_) => continue,
+                Node::Text(text) => (NodeKind::Text, text.as_str()),
+                Node::Comment(text) => (NodeKind::Comment, text.as_str()),
+                Node::Cdata(text) => (NodeKind::Cdata, text.as_str()),
+                Node::Doctype(text) => (NodeKind::Doctype, text.as_str()),
+                Node::ProcessingInstruction(target, _) => {
+                    (NodeKind::ProcessingInstruction, target.as_str())
+                }
+            };
+            others.push(other);
+        }
+
+        for (index, node) in nodes.iter().enumerate() {
+            let Node::Element(element) = node else {
+                continue;
+            };
+            path.push(index);
+            let key = match element.span {
+                Some(span) => Key::Source(span.start),
+                None => Key::Path(path.clone()),
+            };
+            self.elements.insert(
+                key.clone(),
+                Entry {
+                    element,
+                    parent: parent.clone(),
+                },
+            );
+            self.collect(&element.children, Some(key), path);
+            path.pop();
+        }
+    }
+
+    fn element_ref(&self, key: &Option<Key>) -> Option<ElementRef> {
+        key.as_ref()
+            .and_then(|key| self.elements.get(key))
+            .map(|entry| ElementRef::new(entry.element))
+    }
+}
+
+/// Lists the edits that turn `before` into `after`, in document order.
+pub fn diff_documents(before: &Document, after: &Document) -> Vec<Edit> {
+    let old = Snapshot::new(before);
+    let new = Snapshot::new(after);
+    let mut edits = Vec::new();
+
+    for (key, entry) in &old.elements {
+        let element = ElementRef::new(entry.element);
+        let Some(current) = new.elements.get(key) else {
+            // Report only the outermost removed element.
+            let parent_removed = entry
+                .parent
+                .as_ref()
+                .is_some_and(|parent| !new.elements.contains_key(parent));
+            if !parent_removed {
+                edits.push(Edit::ElementRemoved { element });
+            }
+            continue;
+        };
+
+        if current.element.name != entry.element.name {
+            edits.push(Edit::ElementRenamed {
+                element: element.clone(),
+                name: current.element.name.clone(),
+            });
+        }
+        if current.parent != entry.parent {
+            edits.push(Edit::ElementMoved {
+                element: element.clone(),
+                from: old.element_ref(&entry.parent),
+                to: new.element_ref(&current.parent),
+            });
+        }
+        diff_attributes(&element, entry.element, current.element, &mut edits);
+    }
+
+    for (key, entry) in &new.elements {
+        let parent_added = entry
+            .parent
+            .as_ref()
+            .is_some_and(|parent| !old.elements.contains_key(parent));
+        if !old.elements.contains_key(key) && !parent_added {
+            edits.push(Edit::ElementAdded {
+                element: ElementRef::new(entry.element),
+                parent: new.element_ref(&entry.parent),
+            });
+        }
+    }
+
+    for (parent, old_nodes) in &old.nodes {
+        // Children of removed elements went with them.
+        let Some(new_nodes) = new.nodes.get(parent) else {
+            continue;
+        };
+        let parent_ref = old.element_ref(parent);
+        for (kind, content) in multiset_difference(old_nodes, new_nodes) {
+            edits.push(Edit::NodeRemoved {
+                parent: parent_ref.clone(),
+                kind,
+                content: content.to_string(),
+            });
+        }
+        for (kind, content) in multiset_difference(new_nodes, old_nodes) {
+            edits.push(Edit::NodeAdded {
+                parent: parent_ref.clone(),
+                kind,
+                content: content.to_string(),
+            });
+        }
+    }
+
+    edits
+}
+
+fn diff_attributes(element: &ElementRef, old: &Element, new: &Element, edits: &mut Vec<Edit>) {
+    for (name, value) in &old.attributes {
+        match new.attributes.get(name) {
+            None => edits.push(Edit::AttributeRemoved {
+                element: element.clone(),
+                name: name.clone(),
+                value: value.clone(),
+            }),
+            Some(current) if current != value => edits.push(Edit::AttributeChanged {
+                element: element.clone(),
+                name: name.clone(),
+                old: value.clone(),
+                new: current.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (name, value) in &new.attributes {
+        if !old.attributes.contains_key(name) {
+            edits.push(Edit::AttributeAdded {
+                element: element.clone(),
+                name: name.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+/// Items of `items` left over after removing one match per item of `other`.
+fn multiset_difference<'a>(
+    items: &[(NodeKind, &'a str)],
+    other: &[(NodeKind, &'a str)],
+) -> Vec<(NodeKind, &'a str)> {
+    let mut remaining = other.to_vec();
+    items
+        .iter()
+        .filter(
+            |item| match remaining.iter().position(|other| other == *item) {
+                Some(index) => {
+                    remaining.swap_remove(index);
+                    false
+                }
+                None => true,
+            },
+        )
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn descriptions(edits: &[Edit]) -> Vec<String> {
+        edits.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_diff_reports_removed_and_rewritten() {
+        let before = parser::parse(
+            "<svg>\n<title>x</title>\n<g fill=\"red\"><path d=\"M 0 0 L 10 10\"/></g></svg>",
+        )
+        .unwrap();
+        let mut after = before.clone();
+        let Node::Element(svg) = &mut after.root[0] else {
+            panic!("expected <svg>");
+        };
+        svg.children
+            .retain(|node| !matches!(node, Node::Element(e) if e.name == "title"));
+        let Some(Node::Element(group)) = svg.children.pop() else {
+            panic!("expected <g>");
+        };
+        let Node::Element(mut path) = group.children[0].clone() else {
+            panic!("expected <path>");
+        };
+        path.attributes
+            .insert("d".to_string(), "M0 0l10 10".to_string());
+        path.attributes
+            .insert("fill".to_string(), "red".to_string());
+        svg.children.push(Node::Element(path));
+
+        assert_eq!(
+            descriptions(&diff_documents(&before, &after)),
+            [
+                "removed <title> (2:1)",
+                "removed <g> (3:1)",
+                "moved <path> (3:15) from <g> (3:1) to <svg> (1:1)",
+                "rewrote d on <path> (3:15): \"M 0 0 L 10 10\" -> \"M0 0l10 10\"",
+                "added fill=\"red\" to <path> (3:15)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trace_records_each_plugin() {
+        let input = "<svg><desc>d</desc><rect x=\"0\" width=\"10.000\"/></svg>";
+        let (output, trace) = optimize_with_trace(input, &OptimizeOptions::default()).unwrap();
+        let changes: Vec<(&str, isize)> = trace
+            .changes()
+            .map(|step| (step.plugin.as_str(), step.bytes_saved()))
+            .collect();
+
+        assert_eq!(output, crate::optimize(input));
+        assert!(changes.contains(&("removeDesc", 14)), "{changes:?}");
+        let total: isize = trace.steps.iter().map(TraceStep::bytes_saved).sum();
+        assert_eq!(total as usize, input.len() - output.len());
+    }
+}
@@ -1,4 +1,22 @@
 use indexmap::IndexMap;
+use std::sync::Arc;
+
+pub const SVG_NS: &str = "http://www.w3.org/2000/svg";
+pub const XLINK_NS: &str = "http://www.w3.org/1999/xlink";
+pub const XML_NS: &str = "http://www.w3.org/XML/1998/namespace";
+pub const XMLNS_NS: &str = "http://www.w3.org/2000/xmlns/";
+
+/// Text and attribute values keep references to undeclared entities (say
+/// `&nbsp;`) as `ENTITY_REF_START name ENTITY_REF_END`, so the printer can
+/// tell them apart from a literal `&` and write them back unchanged. These
+/// are Unicode noncharacters; a literal U+FDD0 in the source is stored as
+/// the pair with an empty name.
+pub const ENTITY_REF_START: char = '\u{FDD0}';
+pub const ENTITY_REF_END: char = '\u{FDD1}';
+
+/// Namespace bindings in scope for an element, keyed by prefix (`""` is the
+/// default namespace). Elements that declare nothing share their parent's map.
+pub type Namespaces = Arc<IndexMap<String, String>>;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Node {
@@ -10,11 +28,41 @@ pub enum Node {
     ProcessingInstruction(String, Option<String>),
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// A location in the parsed input. `start..end` is a byte range, `line` and
+/// `column` (1-based, in characters) point at `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourceSpan {
+    pub start: usize,
+    pub end: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Clone)]
 pub struct Element {
     pub name: String,
     pub attributes: IndexMap<String, String>,
     pub children: Vec<Node>,
+    /// Bindings in scope when the element was parsed. This is a snapshot:
+    /// adding, removing or rewriting `xmlns` attributes, or moving the element
+    /// under another parent, does not update it. Empty for elements built in
+    /// code unless the caller fills it in.
+    pub namespaces: Namespaces,
+    /// From the opening `<` to the end of the closing tag. `None` for
+    /// elements built in code.
+    pub span: Option<SourceSpan>,
+    /// Spans of parsed attributes (`name="value"`), keyed like `attributes`.
+    pub attribute_spans: IndexMap<String, SourceSpan>,
+}
+
+/// Source positions are ignored, so documents compare equal by content.
+impl PartialEq for Element {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.attributes == other.attributes
+            && self.children == other.children
+            && self.namespaces == other.namespaces
+    }
 }
 
 impl Element {
@@ -23,16 +71,170 @@ impl Element {
             name: name.into(),
             attributes: IndexMap::new(),
             children: Vec::new(),
+            namespaces: Namespaces::default(),
+            span: None,
+            attribute_spans: IndexMap::new(),
+        }
+    }
+
+    pub fn prefix(&self) -> Option<&str> {
+        split_qname(&self.name).0
+    }
+
+    pub fn local_name(&self) -> &str {
+        split_qname(&self.name).1
+    }
+
+    /// Resolves `prefix` against the bindings in scope. The `xml` and `xmlns`
+    /// prefixes are always bound.
+    ///
+    /// Lookups go through [`Element::namespaces`], so they reflect the source
+    /// document rather than later edits; on elements built in code only the
+    /// fixed prefixes resolve, and unprefixed names fall back to SVG.
+    pub fn lookup_namespace(&self, prefix: &str) -> Option<&str> {
+        match prefix {
+            "xml" => Some(XML_NS),
+            "xmlns" => Some(XMLNS_NS),
+            _ => self.namespaces.get(prefix).map(String::as_str),
+        }
+    }
+
+    /// The namespace URI of the element name. Unprefixed names without a
+    /// default namespace in scope are treated as SVG, since inline SVG
+    /// usually omits the declaration.
+    pub fn namespace_uri(&self) -> Option<&str> {
+        match self.prefix() {
+            Some(prefix) => self.lookup_namespace(prefix),
+            None => Some(self.lookup_namespace("").unwrap_or(SVG_NS)),
+        }
+    }
+
+    /// The namespace URI of attribute `key`. Unprefixed attributes are in no
+    /// namespace, except for `xmlns` itself.
+    pub fn attribute_namespace_uri(&self, key: &str) -> Option<&str> {
+        match split_qname(key) {
+            (Some(prefix), _) => self.lookup_namespace(prefix),
+            (None, "xmlns") => Some(XMLNS_NS),
+            (None, _) => None,
         }
     }
+
+    pub fn is_svg(&self) -> bool {
+        self.namespace_uri() == Some(SVG_NS)
+    }
+
+    pub fn attribute_span(&self, key: &str) -> Option<SourceSpan> {
+        self.attribute_spans.get(key).copied()
+    }
+}
+
+/// Splits `prefix:local` into its parts.
+pub fn split_qname(name: &str) -> (Option<&str>, &str) {
+    match name.split_once(':') {
+        Some((prefix, local)) => (Some(prefix), local),
+        None => (None, name),
+    }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct Document {
     pub root: Vec<Node>, // Usually contains one root Element, but can have comments/doctype before it
 }
 
+impl Default for Document {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Document {
     pub fn new() -> Self {
         Self { root: Vec::new() }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    fn find<'a>(elem: &'a Element, name: &str) -> Option<&'a Element> {
+        if elem.name == name {
+            return Some(elem);
+        }
+        elem.children.iter().find_map(|child| match child {
+            Node::Element(child) => find(child, name),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_namespace_resolution() {
+        let doc = parser::parse(
+            r##"<svg:svg xmlns:svg="http://www.w3.org/2000/svg" xmlns:l="http://www.w3.org/1999/xlink"><svg:use l:href="#a" xml:space="preserve"/><g xmlns="urn:other"><x/></g></svg:svg>"##,
+        )
+        .unwrap();
+        let Some(Node::Element(root)) = doc.root.first() else {
+            panic!("expected root element");
+        };
+
+        assert!(root.is_svg());
+        assert_eq!(root.local_name(), "svg");
+        let use_elem = find(root, "svg:use").unwrap();
+        assert!(use_elem.is_svg());
+        assert_eq!(use_elem.attribute_namespace_uri("l:href"), Some(XLINK_NS));
+        assert_eq!(use_elem.attribute_namespace_uri("xml:space"), Some(XML_NS));
+        assert_eq!(use_elem.attribute_namespace_uri("width"), None);
+        assert_eq!(find(root, "x").unwrap().namespace_uri(), Some("urn:other"));
+    }
+
+    #[test]
+    fn test_namespace_redeclaration_is_scoped() {
+        let doc = parser::parse(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink"><g xmlns:xlink="urn:not-xlink"><use xlink:href="#a"/></g><use xlink:href="#b"/></svg>"##,
+        )
+        .unwrap();
+        let Some(Node::Element(root)) = doc.root.first() else {
+            panic!("expected root element");
+        };
+        let Node::Element(group) = &root.children[0] else {
+            panic!("expected <g>");
+        };
+        let (Node::Element(inner), Node::Element(outer)) = (&group.children[0], &root.children[1])
+        else {
+            panic!("expected <use> elements");
+        };
+
+        assert_eq!(
+            inner.attribute_namespace_uri("xlink:href"),
+            Some("urn:not-xlink")
+        );
+        assert_eq!(outer.attribute_namespace_uri("xlink:href"), Some(XLINK_NS));
+        assert!(Element::new("rect").is_svg());
+    }
+
+    #[test]
+    fn test_source_spans() {
+        let input = "<svg>\n  <rect x=\"1\"\n        y=\"2\"/>\n</svg>";
+        let doc = parser::parse(input).unwrap();
+        let Some(Node::Element(root)) = doc.root.first() else {
+            panic!("expected root element");
+        };
+        let rect = find(root, "rect").unwrap();
+
+        let span = rect.span.unwrap();
+        assert_eq!(
+            &input[span.start..span.end],
+            "<rect x=\"1\"\n        y=\"2\"/>"
+        );
+        assert_eq!((span.line, span.column), (2, 3));
+        let y = rect.attribute_span("y").unwrap();
+        assert_eq!(&input[y.start..y.end], "y=\"2\"");
+        assert_eq!((y.line, y.column), (3, 9));
+        assert_eq!(root.span.unwrap().end, input.len());
+
+        // Positions don't take part in equality.
+        let moved = parser::parse("<svg>\n  <rect x=\"1\" y=\"2\"/>\n</svg>").unwrap();
+        assert_eq!(moved.root[0], doc.root[0]);
+    }
+}
@@ -0,0 +1,91 @@
+//! JavaScript API beyond the plain `optimize` export.
+
+use crate::bindings::{optimize, plugin_catalog, OptimizeResult, Options};
+use crate::error::Error;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export type PluginParams = Record<string, boolean | number | string>;
+
+export interface OptimizeOptions {
+  /** Numeric precision. Defaults to 3. */
+  precision?: number;
+  /** Plugins to turn on in addition to the defaults. */
+  enable?: string[];
+  /** Plugins to turn off. */
+  disable?: string[];
+  /** Pretty print instead of minifying. */
+  pretty?: boolean;
+  /** Run exactly these plugins in this order. */
+  order?: string[];
+  /** Re-run the pipeline until the output stops shrinking. */
+  multipass?: boolean;
+  maxPasses?: number;
+  /** Per-plugin toggles or parameters, as in `svgtidy.config.json`. */
+  plugins?: Record<string, boolean | PluginParams>;
+}
+
+export interface OptimizeResult {
+  /** The optimized SVG, or `null` if optimization failed. */
+  data: string | null;
+  error: string | null;
+  /** Input size in bytes (UTF-8). */
+  originalSize: number;
+  /** Output size in bytes (UTF-8), 0 on error. */
+  optimizedSize: number;
+}
+
+/**
+ * Optimizes `svg` with the given options. Errors (invalid SVG, unknown
+ * plugins, bad options) are reported in `error` rather than thrown.
+ */
+export function optimizeWithOptions(svg: string, options?: OptimizeOptions): OptimizeResult;
+
+export interface PluginParamInfo {
+  name: string;
+  type: "boolean" | "integer";
+  description: string;
+  /** `null` when the parameter follows the global `precision`. */
+  default: boolean | number | null;
+}
+
+export interface PluginInfo {
+  name: string;
+  description: string;
+  enabledByDefault: boolean;
+  params: PluginParamInfo[];
+}
+
+/** The built-in plugins in their default order. */
+export function listPlugins(): PluginInfo[];
+"#;
+
+/// Optimizes `svg` with the given options. Errors (invalid SVG, unknown
+/// plugins, bad options) are reported in `error` rather than thrown.
+#[wasm_bindgen(js_name = optimizeWithOptions, skip_typescript)]
+pub fn optimize_with_options_js(
+    svg: &str,
+    options: Option<JsValue>,
+) -> std::result::Result<JsValue, JsError> {
+    let options = match options {
+        Some(options) if !options.is_undefined() && !options.is_null() => {
+            // Going through JSON makes `deny_unknown_fields` see every key.
+            serde_wasm_bindgen::from_value(options)
+                .map_err(|e| Error::invalid_config(e.to_string()))
+                .and_then(Options::from_json_value)
+        }
+        _ => Ok(Options::default()),
+    };
+    let output = options.and_then(|options| optimize(svg, &options));
+    let result = OptimizeResult::new(svg, output);
+    Ok(result.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// Lists the built-in plugins so UIs can offer toggles and parameters
+/// without hard-coding them.
+#[wasm_bindgen(js_name = listPlugins, skip_typescript)]
+pub fn list_plugins_js() -> std::result::Result<JsValue, JsError> {
+    Ok(plugin_catalog().serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
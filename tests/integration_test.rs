@@ -3,7 +3,7 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use svgtidy::parser;
 use svgtidy::pipeline::OptimizeOptions;
-use svgtidy::{optimize, optimize_with_options};
+use svgtidy::{optimize, optimize_with_options, Error};
 
 #[test]
 fn test_svg_cases() {
@@ -67,6 +67,23 @@ fn test_disable_root_level_cleanup_plugins_preserves_nodes() {
     );
 }
 
+#[test]
+fn test_optimize_with_options_reports_structured_errors() {
+    let err = optimize_with_options("<svg>\n<rect", &OptimizeOptions::default()).unwrap_err();
+    assert!(matches!(err, Error::Parse { line: 2, .. }), "{err:?}");
+
+    let mut options = OptimizeOptions::default();
+    options.enable.insert("noSuchPlugin".to_string());
+    let err = optimize_with_options("<svg/>", &options).unwrap_err();
+    assert!(matches!(&err, Error::UnknownPlugin(names) if names == &["noSuchPlugin"]));
+    assert_eq!(err.to_string(), "unknown plugin(s): noSuchPlugin");
+}
+
+#[test]
+fn test_optimize_returns_input_on_error() {
+    assert_eq!(optimize("<svg><g></svg>"), "<svg><g></svg>");
+}
+
 fn svg_case_paths() -> Vec<PathBuf> {
     let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
     let test_cases_dir = Path::new(&manifest_dir).join("test-cases");
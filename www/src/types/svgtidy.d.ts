@@ -1,3 +1,39 @@
 declare module 'svgtidy' {
+  export type PluginParams = Record<string, boolean | number | string>;
+
+  export interface OptimizeOptions {
+    precision?: number;
+    enable?: string[];
+    disable?: string[];
+    pretty?: boolean;
+    order?: string[];
+    multipass?: boolean;
+    maxPasses?: number;
+    plugins?: Record<string, boolean | PluginParams>;
+  }
+
+  export interface OptimizeResult {
+    data: string | null;
+    error: string | null;
+    originalSize: number;
+    optimizedSize: number;
+  }
+
+  export interface PluginParamInfo {
+    name: string;
+    type: 'boolean' | 'integer';
+    description: string;
+    default: boolean | number | null;
+  }
+
+  export interface PluginInfo {
+    name: string;
+    description: string;
+    enabledByDefault: boolean;
+    params: PluginParamInfo[];
+  }
+
   export function optimize(svg: string): string;
+  export function optimizeWithOptions(svg: string, options?: OptimizeOptions): OptimizeResult;
+  export function listPlugins(): PluginInfo[];
 }